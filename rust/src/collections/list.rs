@@ -50,10 +50,64 @@ impl Listing for List {
 	fn _to_vec(&self) -> Vec<Object> {
 		self.0.clone()
 	}
+
+	fn get(&self, index: usize) -> Option<Object> {
+		self.0.get(index).cloned()
+	}
+
+	fn set(&mut self, index: usize, obj: Object) -> Option<Object> {
+		self.0.get_mut(index).map(|old| std::mem::replace(old, obj))
+	}
+
+	fn insert(&mut self, index: usize, obj: Object) {
+		self.0.insert(index, obj)
+	}
+
+	fn remove(&mut self, index: usize) -> Option<Object> {
+		if index < self.0.len() {
+			Some(self.0.remove(index))
+		} else {
+			None
+		}
+	}
+
+	fn splice(&mut self, range: std::ops::Range<usize>, replacement: Vec<Object>) -> Vec<Object> {
+		self.0.splice(range, replacement).collect()
+	}
 }
 
 impl FromIterator<Object> for List {
 	fn from_iter<T: IntoIterator<Item=Object>>(iter: T) -> List {
 		List::new(Vec::from_iter(iter))
 	}
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+	use super::List;
+	use crate::collections::Listing;
+	use crate::IntoObject;
+
+	#[test]
+	fn set_insert_and_remove() {
+		let mut list = List::new(vec![1.into_object(), 2.into_object()]);
+
+		assert_eq!(list.set(0, 3.into_object()), Some(1.into_object()));
+		assert_eq!(list.set(2, 4.into_object()), None);
+
+		list.insert(2, 5.into_object());
+		assert_eq!(list._to_vec(), vec![3.into_object(), 2.into_object(), 5.into_object()]);
+
+		assert_eq!(list.remove(1), Some(2.into_object()));
+		assert_eq!(list.remove(2), None);
+		assert_eq!(list.get(1), Some(5.into_object()));
+	}
+
+	#[test]
+	fn splice() {
+		let mut list = (1..=4).map(IntoObject::into_object).collect::<List>();
+
+		assert_eq!(list.splice(1..3, vec![5.into_object()]), vec![2.into_object(), 3.into_object()]);
+		assert_eq!(list._to_vec(), vec![1.into_object(), 5.into_object(), 4.into_object()]);
+		assert_eq!(list.splice(0..0, vec![]), vec![]);
+	}
+}
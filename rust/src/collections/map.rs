@@ -24,17 +24,6 @@ impl Map {
 	pub fn iter(&self) -> impl Iterator<Item=&Pair> {
 		self.data.iter()
 	}
-
-	pub fn keys(&self) -> impl Iterator<Item=&Object> {
-		struct KeyIter<'a>(std::slice::Iter<'a, Pair>);
-		impl<'a> Iterator for KeyIter<'a> {
-			type Item = &'a Object;
-			fn next(&mut self) -> Option<&'a Object> {
-				self.0.next().map(|(k, _)| k)
-			}
-		}
-		KeyIter(self.data.iter())
-	}
 }
 
 impl Debug for Map {
@@ -91,7 +80,11 @@ impl Mapping for Map {
 	}
 
 	fn has(&self, key: &Object) -> bool {
-		self.keys().any(|k| k == key)
+		self.iter().any(|(k, _)| k == key)
+	}
+
+	fn keys(&self) -> Vec<Object> {
+		self.iter().map(|(k, _)| k.clone()).collect()
 	}
 }
 
@@ -26,6 +26,16 @@ pub trait Mapping : Collection {
 	fn set(&mut self, key: Object, val: Object) -> Option<Object>;
 	fn del(&mut self, key: &Object) -> Option<Object>;
 	fn has(&self, key: &Object) -> bool;
+	/// The keys set directly on this mapping, without any that come from parents
+	fn keys(&self) -> Vec<Object>;
+
+	/// The key-value pairs for each of `keys`
+	fn pairs(&self) -> Vec<(Object, Object)> {
+		self.keys()
+			.into_iter()
+			.filter_map(|key| self.get(&key).map(|val| (key, val)))
+			.collect()
+	}
 
 	fn get_attr(&self, attr: &'static str) -> Option<Object> {
 		self.get(&attr.into_object())
@@ -48,6 +58,44 @@ pub trait Listing : Collection {
 	fn push(&mut self, obj: Object);
 	fn pop(&mut self) -> Option<Object>;
 	fn _to_vec(&self) -> Vec<Object>;
+
+	fn get(&self, index: usize) -> Option<Object>;
+	/// Replaces the element at `index`, returning the old one. Nothing happens if `index` is out of bounds
+	fn set(&mut self, index: usize, obj: Object) -> Option<Object>;
+	/// Inserts `obj` before `index`, which may be the length; panics if `index` is past that
+	fn insert(&mut self, index: usize, obj: Object);
+	fn remove(&mut self, index: usize) -> Option<Object>;
+	/// Replaces the elements in `range` with `replacement`, returning the old ones; panics if
+	/// `range` is out of bounds
+	fn splice(&mut self, range: std::ops::Range<usize>, replacement: Vec<Object>) -> Vec<Object>;
+}
+
+impl dyn Listing {
+	/// Iterates over the elements without cloning the whole listing first
+	pub fn iter(&self) -> Elements<'_> {
+		Elements { listing: self, index: 0 }
+	}
+}
+
+/// An iterator over a `Listing`'s elements, which clones each one as it's reached
+pub struct Elements<'a> {
+	listing: &'a dyn Listing,
+	index: usize
+}
+
+impl Iterator for Elements<'_> {
+	type Item = Object;
+
+	fn next(&mut self) -> Option<Object> {
+		let obj = self.listing.get(self.index)?;
+		self.index += 1;
+		Some(obj)
+	}
+
+	fn size_hint(&self) -> (usize, Option<usize>) {
+		let remaining = self.listing.len().saturating_sub(self.index);
+		(remaining, Some(remaining))
+	}
 }
 
 impl<T: Collection + ?Sized> Collection for Shared<T> {
@@ -84,4 +132,9 @@ impl<T: Mapping + ?Sized> Mapping for Shared<T> {
 	fn has(&self, key: &Object) -> bool {
 		self.read().has(key)
 	}
+
+	#[inline]
+	fn keys(&self) -> Vec<Object> {
+		self.read().keys()
+	}
 }
\ No newline at end of file
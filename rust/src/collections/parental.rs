@@ -22,6 +22,11 @@ impl ParentalMap {
 	pub fn new_default(parent: InitFunc) -> ParentalMap {
 		ParentalMap::new_mapped(parent, Map::default())
 	}
+
+	/// An empty map whose parent already exists
+	pub fn new_with_parent(parent: Object) -> ParentalMap {
+		ParentalMap { parent: ParentalObject::new_initialized(parent), map: Map::default() }
+	}
 }
 
 impl<M: Mapping> ParentalMap<M> {
@@ -116,4 +121,10 @@ impl<M: Mapping> Mapping for ParentalMap<M> {
 
 		self.map.has(key) || self.parent.has(key)
 	}
+
+	#[inline]
+	fn keys(&self) -> Vec<Object> {
+		// like `len`, the parent's keys aren't included
+		self.map.keys()
+	}
 }
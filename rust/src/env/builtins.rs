@@ -2,6 +2,31 @@ use lazy_static::lazy_static;
 use crate::{Object, Error, Environment};
 use std::io::{self, Write, Read};
 
+// the name a loop was given, if any
+fn to_label(label: &Object) -> Result<Option<String>, Error> {
+	if label.is_null() {
+		Ok(None)
+	} else {
+		Ok(Some(label.into_text()?.into_inner()))
+	}
+}
+
+/// Runs `body` once for a loop called `label`, returning the value to stop with if it `break`s.
+///
+/// `break`s and `continue`s for other loops are passed on, so that the loops they're for get them.
+fn iterate(body: &Object, args: &[&Object], label: Option<&str>) -> Result<Option<Object>, Error> {
+	let is_ours = |target: &Option<String>| target.is_none() || target.as_ref().map(String::as_str) == label;
+
+	match body.call_attr("()", args) {
+		Ok(_) | Err(Error::NothingToReturn) => Ok(None),
+		Err(Error::Continue { label: target }) =>
+			if is_ours(&target) { Ok(None) } else { Err(Error::Continue { label: target }) },
+		Err(Error::Break { label: target, value }) =>
+			if is_ours(&target) { Ok(Some(value)) } else { Err(Error::Break { label: target, value }) },
+		Err(other) => Err(other)
+	}
+}
+
 // to make it easier on my eyes
 macro_rules! builtins {
 	($($args:tt)*) => {
@@ -23,27 +48,54 @@ builtins! {
 		}
 	}
 
-	fn "while" (@cond, body) {
+	// loops can be given a `label`, so that `break` and `continue` can be used on them from inside
+	// other loops. they return the value they were `break`ed with, or `null`.
+	fn "while" (@cond, body; label=Object::new_null()) {
+		let label = to_label(&label)?;
 		while cond.call_attr("()", &[])?.into_bool()?.into_inner() {
-			match body.call_attr("()", &[]) {
-				Ok(_) => {},
-				Err(crate::Error::NothingToReturn) => {},
-				Err(other) => return Err(other)
+			if let Some(value) = iterate(body, &[], label.as_ref().map(String::as_str))? {
+				return Ok(value);
 			}
 		}
 		Object::new_null()
 	}
 
-	fn "loop" (@body) {
+	fn "loop" (@body; label=Object::new_null()) {
+		let label = to_label(&label)?;
 		loop {
-			match body.call_attr("()", &[]) {
-				Ok(_) => {},
-				Err(crate::Error::NothingToReturn) => {},
-				Err(other) => return Err(other)
+			if let Some(value) = iterate(body, &[], label.as_ref().map(String::as_str))? {
+				return Ok(value);
 			}
 		}
 	}
 
+	// calls `body` with each element of `iterable` as `@0`
+	fn "for" (@iterable, body; label=Object::new_null()) {
+		let label = to_label(&label)?;
+		for ele in crate::object::typed::iter::Elements::new(iterable)? {
+			if let Some(value) = iterate(body, &[&ele?], label.as_ref().map(String::as_str))? {
+				return Ok(value);
+			}
+		}
+		Object::new_null()
+	}
+
+	// `break:[value, label]` stops the loop called `label` (or the innermost one), which returns `value`
+	fn "break" (@;value=Object::new_null(); label=Object::new_null()) {
+		return Err(Error::Break { label: to_label(&label)?, value })
+	}
+
+	fn "continue" (@;label=Object::new_null()) {
+		return Err(Error::Continue { label: to_label(&label)? })
+	}
+
+	// `range(stop)` counts up from zero, and `range(start, stop, step)` from `start`. unlike
+	// `start..stop`, `stop` itself is never included.
+	fn "range" (@first; stop=Object::new_null(); step=1.into_object()) {
+		let (start, stop) = if stop.is_null() { (0.into_object(), first.clone()) } else { (first.clone(), stop) };
+		Range::from_objects(function!(), &start, &stop, &step, false)?.into_object()
+	}
+
 	fn "switch" (@case, body) {
 		body.call_attr("()", &[])?.call_attr("[]", &[case])?
 	}
@@ -59,7 +111,131 @@ builtins! {
 		// todo!();
 	} // exit === return
 
-	fn "import" (@_file) { todo!(); }
+	fn "exit" (@;code=0.into_object()) {
+		return Err(crate::Error::Exit(i32::from(code.into_num()?)))
+	}
+
+	// ends iteration from inside a user-defined `next`
+	fn "stop" (_) {
+		return Err(Error::StopIteration)
+	}
+
+	fn "throw" (@value) {
+		return Err(Error::Thrown(value.clone()))
+	}
+
+	// `kind` is either a kind made with `ErrorKind` or the name of one
+	fn "Error" (@message; kind="Error".to_string().into_object(); object=Object::new_null()) {
+		let kind = match crate::object::typed::error::kind_name(&kind) {
+			Some(name) => name,
+			None => kind.into_text()?.into_inner()
+		};
+
+		ErrorObject::new(
+			kind,
+			message.into_text()?.into_inner(),
+			if object.is_null() { None } else { Some(object) }
+		).objectify()
+	}
+
+	// creates a new kind of error derived from `parent`, or returns the existing one. attributes
+	// added to a kind are given to all of its errors.
+	fn "ErrorKind" (@name; parent="Error".to_string().into_object()) {
+		use crate::object::typed::error;
+
+		let parent = match error::kind_name(&parent) {
+			Some(_) => parent,
+			None => error::kind(&parent.into_text()?.into_inner())
+		};
+
+		error::derive_kind(&name.into_text()?.into_inner(), &parent).ok_or_else(|| BadArgument {
+			func: function!(),
+			msg: "a kind with that name already exists with a different parent",
+			position: 0,
+			obj: name.clone()
+		})?
+	}
+
+	fn "Rational" (@numer; denom=1.into_object()) {
+		let whole = |obj: &Object, position: usize| obj.into_num()?.to_bigint().ok_or_else(|| BadArgument {
+			func: function!(),
+			msg: "rationals must be made of whole numbers",
+			position,
+			obj: obj.clone()
+		});
+
+		Rational::new(whole(numer, 0)?, whole(&denom, 1)?)
+			.ok_or_else(|| BadArgument {
+				func: function!(),
+				msg: "denominator is zero",
+				position: 1,
+				obj: denom.clone()
+			})?
+			.into_object()
+	}
+
+	// each argument is either a `[key, value]` list or a map whose pairs are copied over; later
+	// keys overwrite earlier ones.
+	fn "Map" (_) args {
+		let mut pairs = Vec::new();
+
+		for (position, arg) in args.iter().enumerate() {
+			if let Some(map) = arg.downcast_map() {
+				pairs.extend(map.pairs());
+				continue;
+			}
+
+			let pair = arg.into_list()?.into_inner();
+			if pair.len() != 2 {
+				return Err(BadArgument {
+					func: function!(),
+					msg: "pairs must be lists of a key and a value",
+					position,
+					obj: (*arg).clone()
+				});
+			}
+
+			let mut pair = pair.into_iter();
+			pairs.push((pair.next().unwrap(), pair.next().unwrap()));
+		}
+
+		Map::from_pairs(pairs).into_object()
+	}
+
+	// `handler` is called with the error object; `finally` is always run afterwards, even if
+	// `handler` itself fails. control flow (eg `return`) passes through without being caught.
+	fn "try" (@body; handler=Object::new_null(); finally=Object::new_null()) {
+		let result = match body.call_attr("()", &[]) {
+			Err(err) if !err.is_control_flow() && !handler.is_null() => {
+				let err = ErrorObject::caught(&err);
+				handler.call_attr("()", &[&err])
+			},
+			other => other
+		};
+
+		if !finally.is_null() {
+			match finally.call_attr("()", &[]) {
+				Ok(_) | Err(Error::NothingToReturn) => {},
+				Err(err) => return Err(err)
+			}
+		}
+
+		match result {
+			Err(Error::NothingToReturn) => Object::new_null(),
+			other => other?
+		}
+	}
+
+	fn "sprintf" (@template) args {
+		crate::object::typed::format(function!(), template, &args[1..])?.into_object()
+	}
+
+	// runs the file at `path` (relative to the current file) the first time it's imported, and
+	// returns its `$locals`
+	fn "import" (@path) {
+		let name = path.into_text()?.into_inner();
+		crate::env::import::import(&crate::env::import::resolve(&name)?)?
+	}
 
 	fn "disp" (_) args {
 		let sep = Environment::current()
@@ -0,0 +1,183 @@
+//! Loading other files as modules with `import`.
+//!
+//! A module is run once, in its own environment, and importing it gives its `$locals`. Modules are
+//! cached by their canonical path, so every file that imports the same module shares one copy.
+//!
+//! `import:["name"]` looks for `name` (adding `.qs` if it has no extension) in:
+//! 1. the directory of the file doing the importing (or the working directory, if it isn't a file),
+//! 2. each directory in `$module_path`, which starts out as the `QUEST_PATH` environment variable
+//!    and can be changed by scripts,
+//! 3. the standard library that's compiled into the binary, for names like `std/math` and `std/json`.
+use crate::{Object, Error, Result, IntoObject, Environment, Shared};
+use crate::parse::{Parser, Location};
+use crate::collections::Mapping;
+use std::collections::HashMap;
+use std::ffi::OsString;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, RwLock};
+use lazy_static::lazy_static;
+
+// the modules in the standard library, by the name they're imported as
+const STDLIB: &[(&str, &str)] = &[
+	("std/math", include_str!("../../std/math.qs")),
+	("std/json", include_str!("../../std/json.qs"))
+];
+
+lazy_static! {
+	static ref MODULES: Mutex<HashMap<PathBuf, Object>> = Mutex::new(HashMap::new());
+	// the modules that are currently being run, outermost first
+	static ref LOADING: Mutex<Vec<PathBuf>> = Mutex::new(Vec::new());
+	static ref MODULE_PATH: RwLock<Object> = RwLock::new(initial_module_path(std::env::var_os("QUEST_PATH")));
+}
+
+// the list that `$module_path` starts as, given the value of `QUEST_PATH`
+fn initial_module_path(quest_path: Option<OsString>) -> Object {
+	quest_path
+		.map(|paths| std::env::split_paths(&paths)
+			.map(|path| path.to_string_lossy().into_owned().into_object())
+			.collect::<Vec<_>>())
+		.unwrap_or_default()
+		.into_object()
+}
+
+/// The list of directories that are searched for modules, which scripts see as `$module_path`
+pub fn module_path() -> Object {
+	MODULE_PATH.read().expect("module path poisoned").clone()
+}
+
+/// Replaces `$module_path`, returning the old one
+pub fn set_module_path(path: Object) -> Object {
+	std::mem::replace(&mut *MODULE_PATH.write().expect("module path poisoned"), path)
+}
+
+/// Where a module's code comes from
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Module {
+	/// A file, whose path is canonical
+	File(PathBuf),
+	/// Part of the standard library
+	Bundled { name: &'static str, code: &'static str }
+}
+
+impl Module {
+	// what the module is cached as
+	fn key(&self) -> PathBuf {
+		match self {
+			Module::File(path) => path.clone(),
+			Module::Bundled { name, .. } => PathBuf::from(format!("<{}>", name))
+		}
+	}
+}
+
+/// Finds the module called `name`, following the steps described in the module docs.
+///
+/// If it can't be found, the error lists everywhere that was looked.
+pub fn resolve(name: &str) -> Result<Module> {
+	let mut file = PathBuf::from(name);
+	if file.extension().is_none() {
+		file.set_extension("qs");
+	}
+
+	let mut dirs = Vec::new();
+
+	if file.is_relative() {
+		let current = Environment::current().read().source();
+		// modules from the standard library aren't in a directory
+		dirs.push(current.as_ref()
+			.filter(|source| source.is_file())
+			.and_then(|source| source.parent())
+			.map(Path::to_owned)
+			.unwrap_or_else(|| PathBuf::from(".")));
+
+		for dir in module_path().into_list()?.into_inner() {
+			dirs.push(PathBuf::from(dir.into_text()?.into_inner()));
+		}
+	} else {
+		dirs.push(PathBuf::new());
+	}
+
+	let mut tried = Vec::new();
+
+	for dir in dirs {
+		let candidate = dir.join(&file);
+		if candidate.is_file() {
+			return candidate.canonicalize().map(Module::File).map_err(Error::IoError);
+		}
+		tried.push(candidate.display().to_string());
+	}
+
+	let bundled_name = name.trim_end_matches(".qs");
+	if let Some(&(name, code)) = STDLIB.iter().find(|&&(name, _)| name == bundled_name) {
+		return Ok(Module::Bundled { name, code });
+	}
+	tried.push(format!("the standard library, as `{}`", bundled_name));
+
+	Err(Error::ModuleNotFound { name: name.to_string(), tried })
+}
+
+// the functions that a module in the standard library is given as locals before it's run
+fn natives(name: &str) -> Option<&'static Object> {
+	match name {
+		"std/json" => Some(&super::json::NATIVES),
+		_ => None
+	}
+}
+
+/// Imports `module`, running it if it hasn't been yet.
+pub fn import(module: &Module) -> Result<Object> {
+	let key = module.key();
+
+	if let Some(cached) = MODULES.lock().expect("modules poisoned").get(&key) {
+		return Ok(cached.clone());
+	}
+
+	{
+		let mut loading = LOADING.lock().expect("loading modules poisoned");
+		if let Some(start) = loading.iter().position(|loading| *loading == key) {
+			let mut cycle = loading[start..].to_vec();
+			cycle.push(key);
+			return Err(Error::ImportCycle(cycle));
+		}
+		loading.push(key.clone());
+	}
+
+	let result = match module {
+		Module::File(path) => crate::parse::execute_file(path, None),
+		Module::Bundled { name, code } => {
+			let location = Location { source: Some(PathBuf::from(format!("<{}>", name))), ..Location::default() };
+			let env = Environment::_new_default_with_stream_and_parent(
+				Shared::new(Parser::from_str_at(code.to_string(), location)),
+				None
+			);
+
+			if let Some(natives) = natives(name) {
+				for key in natives.keys() {
+					let func = natives.get(&key).expect("native was just listed");
+					env.write().set(key, func);
+				}
+			}
+
+			Environment::execute(env)
+		}
+	};
+	LOADING.lock().expect("loading modules poisoned").pop();
+
+	let locals = result?.read().locals().into_object();
+	MODULES.lock().expect("modules poisoned").insert(key, locals.clone());
+	Ok(locals)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn module_path_from_quest_path() {
+		let paths = std::env::join_paths(&["/one", "/two"]).unwrap();
+		assert_eq!(
+			initial_module_path(Some(paths)),
+			vec!["/one".to_string().into_object(), "/two".to_string().into_object()].into_object()
+		);
+		assert_eq!(initial_module_path(None), Vec::<Object>::new().into_object());
+	}
+}
@@ -0,0 +1,298 @@
+//! Converting Quest objects to and from JSON, which `std/json` is built on.
+use crate::{Object, Error, Result, IntoObject};
+use crate::object::typed::{Map, Number};
+use std::fmt::Write;
+use std::iter::Peekable;
+use std::str::{Chars, FromStr};
+use lazy_static::lazy_static;
+
+lazy_static! {
+	/// The functions that `std/json` is written with. Only that module's environment has them.
+	pub static ref NATIVES: Object = function_map!(prefix="json", downcast_fn=__error,
+		fn "__encode__" (@value) {
+			encode(value, function!())?.into_object()
+		}
+
+		fn "__decode__" (@json) {
+			decode(&json.into_text()?.into_inner(), function!())?
+		}
+	);
+}
+
+/// Converts `obj` to JSON. Only null, booleans, finite numbers, texts, lists and maps can be
+/// converted; the keys of maps are converted with `@text`. `func` is used for errors.
+pub fn encode(obj: &Object, func: &'static str) -> Result<String> {
+	let mut json = String::new();
+	encode_into(obj, func, &mut json)?;
+	Ok(json)
+}
+
+fn encode_text(text: &str, json: &mut String) {
+	json.push('"');
+	for chr in text.chars() {
+		match chr {
+			'"' => json.push_str("\\\""),
+			'\\' => json.push_str("\\\\"),
+			'\n' => json.push_str("\\n"),
+			'\r' => json.push_str("\\r"),
+			'\t' => json.push_str("\\t"),
+			chr if chr.is_control() => { write!(json, "\\u{:04x}", chr as u32).expect("writing to a string"); },
+			chr => json.push(chr)
+		}
+	}
+	json.push('"');
+}
+
+fn encode_into(obj: &Object, func: &'static str, json: &mut String) -> Result<()> {
+	let unencodable = |msg: &'static str| Error::BadArgument { func, msg, position: 0, obj: obj.clone() };
+
+	if obj.is_null() {
+		json.push_str("null");
+	} else if let Some(boolean) = obj.downcast_bool() {
+		json.push_str(if boolean.into_inner() { "true" } else { "false" });
+	} else if let Some(num) = obj.downcast_num() {
+		if let Number::Float(float) = num {
+			if !float.is_finite() {
+				return Err(unencodable("JSON can't represent infinity or NaN"));
+			}
+		}
+		write!(json, "{}", num).expect("writing to a string");
+	} else if let Some(text) = obj.downcast_text() {
+		encode_text(&text.into_inner(), json);
+	} else if let Some(list) = obj.downcast_list() {
+		json.push('[');
+		for (idx, ele) in list.into_inner().iter().enumerate() {
+			if idx != 0 {
+				json.push(',');
+			}
+			encode_into(ele, func, json)?;
+		}
+		json.push(']');
+	} else if let Some(map) = obj.downcast_map() {
+		json.push('{');
+		for (idx, (key, val)) in map.pairs().iter().enumerate() {
+			if idx != 0 {
+				json.push(',');
+			}
+			encode_text(&key.into_text()?.into_inner(), json);
+			json.push(':');
+			encode_into(val, func, json)?;
+		}
+		json.push('}');
+	} else {
+		return Err(unencodable("only null, booleans, numbers, texts, lists and maps can be converted to JSON"));
+	}
+
+	Ok(())
+}
+
+/// Converts the JSON in `json` into Quest objects: objects become maps with text keys, and
+/// numbers without a fraction or exponent stay whole. `func` is used for errors.
+pub fn decode(json: &str, func: &'static str) -> Result<Object> {
+	let mut decoder = Decoder { chars: json.chars().peekable(), position: 0, func, json };
+	let obj = decoder.value()?;
+
+	decoder.skip_whitespace();
+	if decoder.chars.peek().is_some() {
+		return Err(decoder.error("trailing characters after JSON"));
+	}
+
+	Ok(obj)
+}
+
+struct Decoder<'a> {
+	chars: Peekable<Chars<'a>>,
+	// how many characters have been read, for errors
+	position: usize,
+	func: &'static str,
+	json: &'a str
+}
+
+impl Decoder<'_> {
+	fn error(&self, msg: &'static str) -> Error {
+		Error::BadArgument {
+			func: self.func,
+			msg,
+			position: self.position,
+			obj: self.json.to_string().into_object()
+		}
+	}
+
+	fn next(&mut self) -> Result<char> {
+		let chr = self.chars.next().ok_or_else(|| self.error("unexpected end of JSON"))?;
+		self.position += 1;
+		Ok(chr)
+	}
+
+	fn skip_whitespace(&mut self) {
+		while self.chars.peek().map(|chr| " \t\n\r".contains(*chr)).unwrap_or(false) {
+			self.next().expect("peeked");
+		}
+	}
+
+	fn expect_word(&mut self, word: &str, obj: Object) -> Result<Object> {
+		for expected in word.chars() {
+			if self.next()? != expected {
+				return Err(self.error("invalid JSON literal"));
+			}
+		}
+		Ok(obj)
+	}
+
+	fn value(&mut self) -> Result<Object> {
+		self.skip_whitespace();
+
+		match self.chars.peek().cloned() {
+			Some('n') => self.expect_word("null", Object::new_null()),
+			Some('t') => self.expect_word("true", true.into_object()),
+			Some('f') => self.expect_word("false", false.into_object()),
+			Some('"') => Ok(self.text()?.into_object()),
+			Some('[') => self.list(),
+			Some('{') => self.map(),
+			Some(chr) if chr == '-' || chr.is_ascii_digit() => self.number(),
+			Some(_) => Err(self.error("invalid JSON")),
+			None => Err(self.error("unexpected end of JSON"))
+		}
+	}
+
+	fn number(&mut self) -> Result<Object> {
+		let mut num = String::new();
+		while let Some(&chr) = self.chars.peek() {
+			if !(chr.is_ascii_digit() || "+-.eE".contains(chr)) {
+				break;
+			}
+			num.push(self.next()?);
+		}
+
+		if num.contains(|chr| ".eE".contains(chr)) {
+			f64::from_str(&num).map(IntoObject::into_object).map_err(|_| self.error("invalid JSON number"))
+		} else {
+			num_bigint::BigInt::from_str(&num)
+				.map(|int| Number::from(int).into_object())
+				.map_err(|_| self.error("invalid JSON number"))
+		}
+	}
+
+	fn text(&mut self) -> Result<String> {
+		let mut text = String::new();
+		self.next()?; // the opening quote
+
+		loop {
+			match self.next()? {
+				'"' => return Ok(text),
+				'\\' => match self.next()? {
+					'"' => text.push('"'),
+					'\\' => text.push('\\'),
+					'/' => text.push('/'),
+					'b' => text.push('\u{8}'),
+					'f' => text.push('\u{c}'),
+					'n' => text.push('\n'),
+					'r' => text.push('\r'),
+					't' => text.push('\t'),
+					'u' => {
+						let mut code = self.hex_escape()?;
+						// characters outside the basic plane are written as two escapes
+						if (0xd800..0xdc00).contains(&code) {
+							if self.next()? != '\\' || self.next()? != 'u' {
+								return Err(self.error("unpaired surrogate in JSON text"));
+							}
+							let low = self.hex_escape()?;
+							if !(0xdc00..0xe000).contains(&low) {
+								return Err(self.error("unpaired surrogate in JSON text"));
+							}
+							code = 0x10000 + ((code - 0xd800) << 10) + (low - 0xdc00);
+						}
+						text.push(std::char::from_u32(code).ok_or_else(|| self.error("invalid unicode escape in JSON"))?);
+					},
+					_ => return Err(self.error("invalid escape in JSON text"))
+				},
+				chr => text.push(chr)
+			}
+		}
+	}
+
+	fn hex_escape(&mut self) -> Result<u32> {
+		let mut code = 0;
+		for _ in 0..4 {
+			let digit = self.next()?.to_digit(16).ok_or_else(|| self.error("invalid unicode escape in JSON"))?;
+			code = code * 16 + digit;
+		}
+		Ok(code)
+	}
+
+	// calls `element` for each element of a list or map that ends with `close`
+	fn elements(&mut self, close: char, mut element: impl FnMut(&mut Self) -> Result<()>) -> Result<()> {
+		self.next()?; // the opening bracket
+		self.skip_whitespace();
+
+		if self.chars.peek() == Some(&close) {
+			self.next()?;
+			return Ok(());
+		}
+
+		loop {
+			element(self)?;
+			self.skip_whitespace();
+
+			match self.next()? {
+				',' => continue,
+				chr if chr == close => return Ok(()),
+				_ => return Err(self.error("expected a comma in JSON"))
+			}
+		}
+	}
+
+	fn list(&mut self) -> Result<Object> {
+		let mut list = Vec::new();
+		self.elements(']', |decoder| {
+			list.push(decoder.value()?);
+			Ok(())
+		})?;
+		Ok(list.into_object())
+	}
+
+	fn map(&mut self) -> Result<Object> {
+		let mut pairs = Vec::new();
+		self.elements('}', |decoder| {
+			decoder.skip_whitespace();
+			if decoder.chars.peek() != Some(&'"') {
+				return Err(decoder.error("JSON keys must be texts"));
+			}
+			let key = decoder.text()?;
+
+			decoder.skip_whitespace();
+			if decoder.next()? != ':' {
+				return Err(decoder.error("expected a colon in JSON"));
+			}
+
+			pairs.push((key.into_object(), decoder.value()?));
+			Ok(())
+		})?;
+		Ok(Map::from_pairs(pairs).into_object())
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn round_trips() {
+		let json = r#"{"a":[1,2.5,"x\n\"y\""],"b":null,"c":true}"#;
+		assert_eq!(encode(&decode(json, "test").unwrap(), "test").unwrap(), json);
+	}
+
+	#[test]
+	fn decodes() {
+		assert_eq!(decode(" [1, -2, 3e2] ", "test").unwrap(), vec![1.into_object(), (-2).into_object(), 300.0.into_object()].into_object());
+		assert_eq!(decode(r#""\u00e9\ud83d\ude00""#, "test").unwrap(), "é😀".to_string().into_object());
+	}
+
+	#[test]
+	fn rejects_invalid() {
+		for json in &["", "[1,", "{1: 2}", "nul", "[1] 2", r#""\q""#] {
+			assert!(decode(json, "test").is_err(), "{:?} was decoded", json);
+		}
+		assert!(encode(&std::f64::INFINITY.into_object(), "test").is_err());
+	}
+}
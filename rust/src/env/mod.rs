@@ -1,9 +1,13 @@
 pub mod builtins;
+pub mod trace;
+pub mod import;
+pub(crate) mod json;
 
 use crate::{Shared, Object, Result, parse::Parser};
 use crate::collections::{Collection, Mapping, Listing};
 use std::fmt::{self, Display, Formatter};
-use std::{mem, sync::RwLock};
+use std::hash::{Hash, Hasher};
+use std::{mem, sync::RwLock, path::PathBuf};
 use lazy_static::lazy_static;
 
 #[derive(Debug)]
@@ -12,7 +16,9 @@ pub struct Environment {
 	parent: Option<Shared<Environment>>,
 	parser: Shared<Parser>,
 	map: Shared<dyn Mapping>,
-	pub(crate) stack: Shared<dyn Listing>
+	pub(crate) stack: Shared<dyn Listing>,
+	// the environment captured by the block this is running, if it's running one
+	closure: Option<Shared<Environment>>
 }
 
 impl Eq for Environment {}
@@ -21,6 +27,12 @@ impl PartialEq for Environment {
 		self.id == other.id
 	}
 }
+
+impl Hash for Environment {
+	fn hash<H: Hasher>(&self, h: &mut H) {
+		self.id.hash(h)
+	}
+}
 impl Environment {
 	fn next_id() -> usize {
 		use std::sync::atomic::{AtomicUsize, Ordering};
@@ -37,7 +49,8 @@ impl Environment {
 			parent: None,
 			parser: Shared::new(Parser::default()),
 			map: Shared::new(crate::collections::Map::empty()),
-			stack: Shared::new(crate::collections::List::empty())
+			stack: Shared::new(crate::collections::List::empty()),
+			closure: None
 		}
 	}
 
@@ -46,10 +59,19 @@ impl Environment {
 			parser, parent,
 			map: map.unwrap_or_else(|| Shared::new(crate::collections::Map::empty())),
 			stack: stack.unwrap_or_else(|| Shared::new(crate::collections::List::empty())),
-			id: Environment::next_id()
+			id: Environment::next_id(),
+			closure: None
 		})
 	}
 
+	/// Creates the environment that a block runs in when it's called, which is a child of the
+	/// environment the block captured (`closure`) rather than of whatever called it.
+	pub fn new_closure(parser: Shared<Parser>, closure: Shared<Environment>, stack: Option<Shared<dyn Listing>>) -> Shared<Environment> {
+		let env = Environment::new(parser, Some(closure.clone()), None, stack);
+		env.write().closure = Some(closure);
+		env
+	}
+
 
 	// im not sure how i want initialization to work, that's why this is underscored
 	pub fn _new_default_with_stream_and_parent(parser: Shared<Parser>, parent: Option<Shared<Environment>>) -> Shared<Environment> {
@@ -70,6 +92,28 @@ impl Environment {
 	}
 
 
+	/// The variables defined directly in this environment, which Quest sees as `$locals`
+	pub fn locals(&self) -> Shared<dyn Mapping> {
+		self.map.clone()
+	}
+
+	/// The environment captured by the innermost block that's running, which scripts see as
+	/// `$closure`. This is `None` outside of blocks, such as at the top of a file.
+	pub fn closure(&self) -> Option<Shared<Environment>> {
+		self.closure.clone().or_else(|| self.parent.as_ref().and_then(|parent| parent.read().closure()))
+	}
+
+	/// The file this environment is running, if its code came from one
+	pub fn source(&self) -> Option<PathBuf> {
+		self.parser.read().location().source.clone()
+	}
+
+	/// Runs `parser` inside of `env`, keeping everything that `env` already has defined.
+	pub fn execute_parser(env: Shared<Environment>, parser: Shared<Parser>) -> Result<Shared<Environment>> {
+		env.write().parser = parser;
+		Environment::execute(env)
+	}
+
 	pub fn execute(env: Shared<Environment>) -> Result<Shared<Environment>> {
 		trace!(target: "execute", "Starting to execute");
 		let mut parser = env.read().parser.clone();
@@ -79,7 +123,7 @@ impl Environment {
 			match Parser::next_unevaluated_object(&parser).transpose() {
 				Err(crate::Error::NothingToReturn) => continue,
 				Err(err) => { Environment::set_current(old_env); return Err(err) },
-				Ok(Some(object)) => match object.evaluate(&parser) {
+				Ok(Some(object)) => match object.evaluate(&parser).map_err(|err| object.locate_err(err)) {
 					Err(crate::Error::NothingToReturn) => continue,
 					Err(crate::Error::Return { env, obj }) => {
 						if env == Environment::current() {
@@ -111,6 +155,7 @@ impl Environment {
 /** CURRENT for env **/
 lazy_static! {
 	static ref CURRENT: RwLock<Shared<Environment>> = RwLock::new(Environment::_new_default_with_stream_and_parent(Shared::new(Parser::default()), None));
+	static ref ARGV: RwLock<Vec<String>> = RwLock::new(Vec::new());
 }
 
 impl Environment {
@@ -124,6 +169,11 @@ impl Environment {
 		env
 	}
 	// pub fn push_env(env: Shared<Environment>) ->
+
+	/// Sets the arguments that scripts see as `$argv`
+	pub fn set_argv(argv: Vec<String>) {
+		*ARGV.write().expect("argv unwritable") = argv;
+	}
 }
 
 impl Display for Environment {
@@ -150,6 +200,16 @@ impl Collection for Environment {
 }
 
 impl Environment {
+	/// Assigns to the special `key`, returning its old value, or gives `val` back if `key` isn't
+	/// a special that can be assigned to.
+	fn set_special(&mut self, key: &str, val: Object) -> std::result::Result<Object, Object> {
+		match key {
+			// `$module_path` is shared by every environment, so assigning to it replaces it everywhere
+			"$module_path" => Ok(import::set_module_path(val)),
+			_ => Err(val)
+		}
+	}
+
 	fn get_special(&self, key: &str) -> Option<Object> {
 		use std::str::FromStr;
 
@@ -174,7 +234,22 @@ impl Environment {
 			if key == "stack" {
 				return Some(self.stack.clone().into_object())
 			} else if key == "locals" {
-				return Some(self.map.clone().into_object())
+				return Some(self.locals().into_object())
+			} else if key == "trace" {
+				return Some(trace::current()
+					.iter()
+					.rev()
+					.filter(|frame| frame.is_block())
+					.map(|frame| frame.to_string().into_object())
+					.collect::<Vec<_>>()
+					.into_object())
+			} else if key == "argv" {
+				let argv = ARGV.read().expect("argv unreadable");
+				return Some(argv.iter().map(|arg| arg.clone().into_object()).collect::<Vec<_>>().into_object())
+			} else if key == "module_path" {
+				return Some(import::module_path())
+			} else if key == "closure" {
+				return self.closure().map(IntoObject::into_object)
 			} else if let Ok(mut nth) = isize::from_str(key) {
 				let mut env_stack = vec![/* and self here in the future */];
 				let mut p = self.parent.clone();
@@ -221,6 +296,15 @@ impl Mapping for Environment {
 	}
 
 	fn set(&mut self, key: Object, val: Object) -> Option<Object> {
+		// like `get`, only `$`-vars need to be checked for specials
+		let val = match key.downcast_var() {
+			Some(var) if var.as_ref().starts_with('$') => match self.set_special(var.as_ref(), val) {
+				Ok(old) => return Some(old),
+				Err(val) => val
+			},
+			_ => val
+		};
+
 		self.map.set(key, val)
 	}
 
@@ -232,4 +316,8 @@ impl Mapping for Environment {
 		// todo: get special for has
 		self.map.has(key) || self.parent.as_ref().map(|parent| parent.has(key)).unwrap_or(false)
 	}
+
+	fn keys(&self) -> Vec<Object> {
+		self.map.keys()
+	}
 }
\ No newline at end of file
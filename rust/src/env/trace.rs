@@ -0,0 +1,107 @@
+use crate::Object;
+use crate::parse::Location;
+use std::collections::VecDeque;
+use std::fmt::{self, Display, Formatter};
+use std::sync::RwLock;
+use lazy_static::lazy_static;
+
+// how many callee names to remember; only the most recent lookups matter
+const CALLEE_NAMES_LIMIT: usize = 64;
+
+/// A call that's currently running.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Frame {
+	/// A call into Quest code; `location` is where the block's body starts and `name` is the
+	/// variable it was called through, if known.
+	Block { location: Location, name: Option<&'static str> },
+	/// A call into a builtin
+	RustFn(&'static str)
+}
+
+lazy_static! {
+	static ref CALL_STACK: RwLock<Vec<Frame>> = RwLock::new(Vec::new());
+	static ref CALLEE_NAMES: RwLock<VecDeque<(usize, &'static str)>> = RwLock::new(VecDeque::new());
+}
+
+/// Pops the frame it was created for when dropped, so frames are popped even when unwinding with `?`
+#[must_use]
+pub struct FrameGuard(());
+
+impl Drop for FrameGuard {
+	fn drop(&mut self) {
+		CALL_STACK.write().expect("call stack unwritable").pop();
+	}
+}
+
+impl Frame {
+	pub fn enter(self) -> FrameGuard {
+		CALL_STACK.write().expect("call stack unwritable").push(self);
+		FrameGuard(())
+	}
+
+	/// Replaces the innermost frame with this one. Used by calls that know more about themselves
+	/// than the `RustFn` that's running them (eg `Block::()` knows its location).
+	pub fn replace_current(self) {
+		if let Some(current) = CALL_STACK.write().expect("call stack unwritable").last_mut() {
+			*current = self;
+		}
+	}
+
+	pub fn is_block(&self) -> bool {
+		match self {
+			Frame::Block { .. } => true,
+			Frame::RustFn(_) => false
+		}
+	}
+}
+
+/// Every frame that's currently running, innermost last.
+pub fn current() -> Vec<Frame> {
+	CALL_STACK.read().expect("call stack unreadable").clone()
+}
+
+/// Remembers that `callee` was just looked up as `name`, so that its frame can be named if it's called.
+pub fn name_callee(callee: &Object, name: &'static str) {
+	let mut names = CALLEE_NAMES.write().expect("callee names unwritable");
+	names.retain(|&(id, _)| id != callee.id());
+	if names.len() == CALLEE_NAMES_LIMIT {
+		names.pop_front();
+	}
+	names.push_back((callee.id(), name));
+}
+
+/// The name `callee` was last looked up as, which is forgotten once it's been taken. This way a
+/// name only ever applies to the call it was looked up for, and not to later calls that reach the
+/// same object some other way.
+pub fn take_callee_name(callee: &Object) -> Option<&'static str> {
+	let mut names = CALLEE_NAMES.write().expect("callee names unwritable");
+	let position = names.iter().rposition(|&(id, _)| id == callee.id())?;
+	names.remove(position).map(|(_, name)| name)
+}
+
+impl Display for Frame {
+	fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+		match self {
+			Frame::Block { location, name: Some(name) } => write!(f, "`{}` at {}", name, location),
+			Frame::Block { location, name: None } => write!(f, "block at {}", location),
+			Frame::RustFn(name) => write!(f, "builtin `{}`", name)
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn callee_names_are_taken_once() {
+		let (callee, other) = (Object::new_null(), Object::new_null());
+		name_callee(&callee, "f");
+		name_callee(&other, "g");
+		name_callee(&callee, "h");
+
+		assert_eq!(take_callee_name(&callee), Some("h"));
+		assert_eq!(take_callee_name(&callee), None);
+		assert_eq!(take_callee_name(&other), Some("g"));
+	}
+}
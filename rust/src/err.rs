@@ -1,7 +1,7 @@
-use crate::{Object, Shared, parse::Parser};
+use crate::{Object, Shared, parse::{Parser, Location}, env::trace::Frame};
 use std::error;
 use std::fmt::{self, Display, Formatter};
-use std::io;
+use std::{fs, io, path::PathBuf};
 
 #[derive(Debug/*, Clone, PartialEq, Eq*/)]
 pub enum Error {
@@ -11,7 +11,9 @@ pub enum Error {
 	},
 	MissingArgument {
 		func: &'static str,
-		pos: usize
+		pos: usize,
+		/// The name of the missing parameter, for blocks that declare them
+		param: Option<&'static str>
 	},
 	ConversionFailure {
 		func: &'static str,
@@ -28,24 +30,272 @@ pub enum Error {
 	ParserError { msg: &'static str, parser: Shared<Parser> },
 	NothingToReturn,
 	Boxed(Box<dyn error::Error>),
-	Return { env: Shared<crate::Environment>, obj: Option<Object> }
+	Return { env: Shared<crate::Environment>, obj: Option<Object> },
+	Exit(i32),
+	/// Raised by an iterator's `next` when it has no more elements
+	StopIteration,
+	/// Stops the innermost loop, or the one called `label`, which then returns `value`
+	Break { label: Option<String>, value: Object },
+	/// Skips to the next iteration of the innermost loop, or the one called `label`
+	Continue { label: Option<String> },
+	/// A module imported itself, possibly indirectly; the paths are in the order they were imported
+	ImportCycle(Vec<PathBuf>),
+	/// `tried` is everywhere the module was looked for, in order
+	ModuleNotFound { name: String, tried: Vec<String> },
+	Thrown(Object),
+	Annotated { err: Box<Error>, location: Option<Location>, trace: Vec<Frame> }
 }
 
 pub type Result<T> = ::std::result::Result<T, Error>;
 pub type ObjResult = Result<Object>;
 
+impl Error {
+	/// Whether this error is used to move around the program, instead of signaling something went wrong
+	pub fn is_control_flow(&self) -> bool {
+		match self {
+			Error::NothingToReturn | Error::Return { .. } | Error::Exit(_) | Error::StopIteration
+				| Error::Break { .. } | Error::Continue { .. } => true,
+			_ => false
+		}
+	}
+
+	/// The name of this error's variant, which Quest sees as the error's `kind`
+	pub fn name(&self) -> &'static str {
+		match self {
+			Error::MissingKey { .. } => "MissingKey",
+			Error::MissingArgument { .. } => "MissingArgument",
+			Error::ConversionFailure { .. } => "ConversionFailure",
+			Error::BadArgument { .. } => "BadArgument",
+			Error::IoError(_) => "IoError",
+			Error::NothingParsableFound(_) => "NothingParsableFound",
+			Error::ParserError { .. } => "ParserError",
+			Error::NothingToReturn => "NothingToReturn",
+			Error::Boxed(_) => "ParseError",
+			Error::Return { .. } => "Return",
+			Error::Exit(_) => "Exit",
+			Error::StopIteration => "StopIteration",
+			Error::Break { .. } => "Break",
+			Error::Continue { .. } => "Continue",
+			Error::ImportCycle(_) => "ImportCycle",
+			Error::ModuleNotFound { .. } => "ModuleNotFound",
+			Error::Thrown(_) => "Thrown",
+			Error::Annotated { err, .. } => err.name()
+		}
+	}
+
+	/// Attaches `location` to this error.
+	///
+	/// Errors that already have a location keep their original (innermost) one, and control flow
+	/// errors are left alone so that they can still be matched on.
+	pub fn located(self, location: Location) -> Error {
+		if self.is_control_flow() || self.location().is_some() {
+			return self;
+		}
+
+		match self {
+			Error::Annotated { err, trace, .. } => Error::Annotated { err, location: Some(location), trace },
+			other => Error::Annotated { err: Box::new(other), location: Some(location), trace: Vec::new() }
+		}
+	}
+
+	/// Attaches the frames of Quest calls that were running when this error happened, innermost last.
+	///
+	/// Like with `located`, only the first (ie innermost) trace is kept.
+	pub fn traced(self, trace: Vec<Frame>) -> Error {
+		if self.is_control_flow() || trace.is_empty() || !self.trace().is_empty() {
+			return self;
+		}
+
+		match self {
+			Error::Annotated { err, location, .. } => Error::Annotated { err, location, trace },
+			other => Error::Annotated { err: Box::new(other), location: None, trace }
+		}
+	}
+
+	pub fn location(&self) -> Option<&Location> {
+		match self {
+			Error::Annotated { location, .. } => location.as_ref(),
+			_ => None
+		}
+	}
+
+	pub fn trace(&self) -> &[Frame] {
+		match self {
+			Error::Annotated { trace, .. } => trace,
+			_ => &[]
+		}
+	}
+
+	/// The error without any location or trace attached
+	pub fn inner(&self) -> &Error {
+		match self {
+			Error::Annotated { err, .. } => err.inner(),
+			other => other
+		}
+	}
+
+	/// Renders this error like rustc does, with a `file:line:col` and the offending line.
+	///
+	/// The line is read from the location's source file; `source` is used for errors in code that
+	/// didn't come from a file, such as `-e` or the repl.
+	pub fn diagnostic(&self, source: Option<&str>) -> String {
+		let mut diagnostic = format!("error: {}", self.inner());
+
+		if let Some(location) = self.location() {
+			diagnostic.push_str(&format!("\n --> {}", location));
+
+			let file = location.source.as_ref().and_then(|path| fs::read_to_string(path).ok());
+			let text = if location.source.is_some() { file.as_ref().map(String::as_str) } else { source };
+
+			if let Some(snippet) = text.and_then(|text| location.snippet(text)) {
+				diagnostic.push('\n');
+				diagnostic.push_str(&snippet);
+			}
+		}
+
+		if !self.trace().is_empty() {
+			diagnostic.push_str("\ntrace (most recent call first):");
+			for frame in self.trace().iter().rev() {
+				diagnostic.push_str(&format!("\n    in {}", frame));
+			}
+		}
+
+		diagnostic
+	}
+}
+
 impl Display for Error {
 	fn fmt(&self, f: &mut Formatter) -> fmt::Result {
-		unimplemented!()
+		match self {
+			Error::MissingKey { key, obj } => write!(f, "Key `{}` is missing from {}", key, obj),
+			Error::MissingArgument { func, pos, param: None } => write!(f, "`{}` is missing argument #{}", func, pos),
+			Error::MissingArgument { func, pos, param: Some(param) } =>
+				write!(f, "`{}` is missing argument #{} (`{}`)", func, pos, param),
+			Error::ConversionFailure { func, obj } => write!(f, "Unable to convert {} using `{}`", obj, func),
+			Error::BadArgument { func, msg, position, obj } =>
+				write!(f, "Bad argument #{} ({}) passed to `{}`: {}", position, obj, func, msg),
+			Error::IoError(err) => write!(f, "I/O error: {}", err),
+			Error::NothingParsableFound(parser) => write!(f, "Nothing parsable found at {:?}", parser.read().beginning()),
+			Error::ParserError { msg, parser } => write!(f, "Parse error at {:?}: {}", parser.read().beginning(), msg),
+			Error::NothingToReturn => write!(f, "Nothing to return"),
+			Error::Boxed(err) => Display::fmt(err, f),
+			Error::Return { .. } => write!(f, "`return` called for an environment that isn't running"),
+			Error::Exit(code) => write!(f, "Exited with code {}", code),
+			Error::StopIteration => write!(f, "`stop` called outside of an iterator"),
+			Error::Break { label: Some(label), .. } => write!(f, "`break` called outside of a loop named `{}`", label),
+			Error::Break { label: None, .. } => write!(f, "`break` called outside of a loop"),
+			Error::Continue { label: Some(label) } => write!(f, "`continue` called outside of a loop named `{}`", label),
+			Error::Continue { label: None } => write!(f, "`continue` called outside of a loop"),
+			Error::ImportCycle(paths) => {
+				write!(f, "Import cycle: ")?;
+				for (i, path) in paths.iter().enumerate() {
+					if i != 0 {
+						write!(f, " -> ")?;
+					}
+					write!(f, "{}", path.display())?;
+				}
+				Ok(())
+			},
+			Error::ModuleNotFound { name, tried } => {
+				write!(f, "Module `{}` not found; looked in:", name)?;
+				for place in tried {
+					write!(f, "\n    {}", place)?;
+				}
+				Ok(())
+			},
+			Error::Thrown(obj) => match obj.into_text() {
+				Ok(text) => Display::fmt(&text, f),
+				Err(_) => write!(f, "{}", obj)
+			},
+			Error::Annotated { err, location: Some(location), .. } => write!(f, "{}: {}", location, err),
+			Error::Annotated { err, location: None, .. } => Display::fmt(err, f)
+		}
 	}
 }
 
 impl error::Error for Error {
 	fn description(&self) -> &str {
-		unimplemented!()
+		match self {
+			Error::MissingKey { .. } => "missing key",
+			Error::MissingArgument { .. } => "missing argument",
+			Error::ConversionFailure { .. } => "conversion failure",
+			Error::BadArgument { .. } => "bad argument",
+			Error::IoError(_) => "i/o error",
+			Error::NothingParsableFound(_) => "nothing parsable found",
+			Error::ParserError { .. } => "parse error",
+			Error::NothingToReturn => "nothing to return",
+			Error::Boxed(_) => "boxed error",
+			Error::Return { .. } => "return to an environment that isn't running",
+			Error::Exit(_) => "exit",
+			Error::StopIteration => "stop iteration",
+			Error::Break { .. } => "break outside of a loop",
+			Error::Continue { .. } => "continue outside of a loop",
+			Error::ImportCycle(_) => "import cycle",
+			Error::ModuleNotFound { .. } => "module not found",
+			Error::Thrown(_) => "thrown",
+			Error::Annotated { err, .. } => err.description()
+		}
+	}
+
+	fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+		match self {
+			Error::IoError(err) => Some(err),
+			Error::Boxed(err) => Some(&**err),
+			Error::Annotated { err, .. } => err.source(),
+			_ => None
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::Error;
+	use std::error::Error as _;
+	use std::io;
+
+	#[test]
+	fn missing_argument_message() {
+		let err = Error::MissingArgument { func: "Number::+", pos: 1, param: None };
+		assert_eq!(err.to_string(), "`Number::+` is missing argument #1");
+	}
+
+	#[test]
+	fn io_error_source() {
+		let err = Error::IoError(io::Error::new(io::ErrorKind::NotFound, "no such file"));
+		assert_eq!(err.to_string(), "I/O error: no such file");
+		assert_eq!(err.source().unwrap().to_string(), "no such file");
+	}
+
+	#[test]
+	fn boxed_error_is_transparent() {
+		let err = Error::Boxed(Box::new(io::Error::new(io::ErrorKind::Other, "inner")));
+		assert_eq!(err.to_string(), "inner");
+		assert!(err.source().is_some());
+	}
+
+	#[test]
+	fn nothing_to_return_has_no_source() {
+		assert!(Error::NothingToReturn.source().is_none());
+	}
+
+	#[test]
+	fn diagnostic_points_at_location() {
+		use crate::parse::Location;
+		let location = Location { source: None, line: 1, col: 4, chars: 0 };
+		let err = Error::MissingArgument { func: "disp", pos: 0, param: None }.located(location);
+
+		assert_eq!(
+			err.diagnostic(Some("x = 1;\ny = disp:();")),
+			"error: `disp` is missing argument #0\n --> <input>:2:5\n |\n2 | y = disp:();\n |     ^"
+		);
 	}
 
-	fn cause(&self) -> Option<&dyn error::Error> {
-		unimplemented!()
+	#[test]
+	fn control_flow_is_never_located() {
+		let location = crate::parse::Location::default();
+		match Error::NothingToReturn.located(location) {
+			Error::NothingToReturn => {},
+			other => panic!("control flow error was located: {:?}", other)
+		}
 	}
 }
\ No newline at end of file
@@ -14,6 +14,7 @@ mod shared;
 mod env;
 mod collections;
 mod err;
+pub mod repl;
 
 pub use self::{
 	shared::Shared,
@@ -133,7 +133,7 @@ macro_rules! _assign_args {
 
 	($args:ident $name:expr, $pos:expr, [$req:ident $($oreq:ident)*] $opt:tt) => {
 		let $req: &$crate::Object = *$args.get($pos).ok_or_else(||
-			$crate::Error::MissingArgument { func: $name, pos: $pos })?;
+			$crate::Error::MissingArgument { func: $name, pos: $pos, param: None })?;
 		_assign_args!($args $name, $pos + 1, [$($oreq)*] $opt);
 	};
 
@@ -1,62 +1,93 @@
-#![allow(unused)]
-use quest::*;
+use quest::{Error, Environment};
+use std::io::{self, Read};
+use std::process;
 
-fn main() {
-    env_logger::init();
-    let res = quest::parse::parse_file("code/test.qs", None);
-    println!("{:?}", res);
+const USAGE: &str = "\
+usage: quest
+       quest [options] <file> [args...]
+       quest [options] -e <code> [args...]
+       quest [options] - [args...]
+
+options:
+  -e <code>   run <code> instead of a file
+  -           read the script from stdin
+  -h, --help  print this message
 
+with no arguments, an interactive session is started.
+any arguments after the script are available to it as `$argv`";
 
-	// match env.execute_stream(stream).map_err(|err| err.to_string())? {
-	// 	Err(err) => println!("err: {:#?}", err),
-	// 	Ok(env) => env.stream.write().pop()
-	// }
-	
-	// Ok(())
+enum Source {
+	Repl,
+	File(String),
+	Inline(String),
+	Stdin
 }
 
+fn parse_args<I: Iterator<Item=String>>(mut args: I) -> Result<Option<(Source, Vec<String>)>, String> {
+	let source = match args.next() {
+		None => Source::Repl,
+		Some(ref arg) if arg == "-h" || arg == "--help" => return Ok(None),
+		Some(ref arg) if arg == "-e" => Source::Inline(args.next().ok_or_else(|| "`-e` requires an argument".to_string())?),
+		Some(ref arg) if arg == "-" => Source::Stdin,
+		Some(ref arg) if arg == "--" => Source::File(args.next().ok_or_else(|| "no script given".to_string())?),
+		Some(ref arg) if arg.starts_with('-') => return Err(format!("unknown option `{}`", arg)),
+		Some(file) => Source::File(file)
+	};
+
+	Ok(Some((source, args.collect())))
+}
+
+fn run(source: &Source) -> quest::Result<()> {
+	let result = match source {
+		Source::File(path) => quest::parse::parse_file(path, None),
+		Source::Inline(code) => quest::parse::parse_str(code.clone(), None),
+		Source::Repl | Source::Stdin => unreachable!("only scripts can be run")
+	};
+
+	match result {
+		// a script that doesn't leave anything on the stack is still a success
+		Ok(_) | Err(Error::NothingToReturn) => Ok(()),
+		Err(err) => Err(err)
+	}
+}
 
-//     let ref three = 3.into_object();
-//     let ref four = 4.into_object();
-//     let ref twenty = 20i32.into_object();
-//     let ref fifteen = 15i32.into_object();
-//     let ref r#true = true.into_object();
-//     let ref r#false = false.into_object();
-//     let ref plus = "+".into_object();
-//     let ref foo = "foo".into_object();
-//     let ref samp = "samp".to_string().into_object();
-
-//     let ref r#if = quest::__BUILTINS_MAP.get(&"if".into_object()).unwrap();
-    
-//     foo.call_attr("=", &[r#false]).unwrap();
-//     let ref bar = 
-//         r#if.call_attr("()", &[&foo.call_attr("()", &[]).unwrap(), twenty, fifteen])
-//             .unwrap()
-//             .call_attr("*", &[three])
-//             .unwrap();
-
-//     println!("{:?}", bar);
-
-//     println!("{:?}", samp.call_attr("*", &[three]));
-
-//     // println!("{:?}", r#if.call_attr("()", &[truth, twenty, fifteen]));
-
-//     // println!("{:?}", foo.call_attr("()", &[]));
-//     // println!("{:?}", quest::Environment::current());
-
-//     // println!("{:#?}", twenty.call(plus, &[fifteen]));
-//     // println!("{:?}", truth.call(plus, &[fifteen]));
-
-//     // println!("{:#?}", parent.get_attr("@parent"));
-//     // basic.set_attr("()", parent.get_attr("*").unwrap());
-//     // // basic.set_attr("bool", basic.get_attr("@bool").unwrap());
-//     // println!("{:?}", fifteen.call_attr("__id__", &[]));
-
-//     // println!("{:?}", twenty.call_attr("()", &[&fifteen]));
-//     // println!("{:?}", fifteen.call_attr("()", &[&twenty]));
-//     // println!("{:?}", twenty.get_attr("()"));
-//     // println!("{:?}", true.into_object().get_attr("()"));
-//     // println!("{:?}", true.into_object().call_attr("()", &[&fifteen]));
-//     // println!("{:?}", twenty.call_attr("+", &[&fifteen]));
-//     // println!("{:?}", twenty.call_attr("@bool", &[]));
-// }
\ No newline at end of file
+fn main() {
+	env_logger::init();
+
+	let (source, argv) = match parse_args(std::env::args().skip(1)) {
+		Ok(Some(args)) => args,
+		Ok(None) => {
+			println!("{}", USAGE);
+			return;
+		},
+		Err(msg) => {
+			eprintln!("quest: {}\n{}", msg, USAGE);
+			process::exit(2);
+		}
+	};
+
+	let source = match source {
+		Source::Repl => process::exit(quest::repl::Repl::new().run()),
+		Source::Stdin => {
+			let mut code = String::new();
+			if let Err(err) = io::stdin().read_to_string(&mut code) {
+				eprintln!("quest: unable to read stdin: {}", err);
+				process::exit(1);
+			}
+			Source::Inline(code)
+		},
+		other => other
+	};
+
+	Environment::set_argv(argv);
+
+	process::exit(match run(&source) {
+		Ok(()) => 0,
+		Err(Error::Exit(code)) => code,
+		Err(err) => {
+			let code = if let Source::Inline(ref code) = source { Some(code.as_str()) } else { None };
+			eprintln!("{}", err.diagnostic(code));
+			1
+		}
+	});
+}
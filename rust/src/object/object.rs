@@ -1,6 +1,6 @@
 use crate::{Shared, Environment};
 use crate::err::{Error, Result};
-use crate::parse::Parser;
+use crate::parse::{Parser, Location};
 use crate::collections::{Collection, Mapping};
 use super::IntoObject;
 
@@ -20,7 +20,8 @@ struct InnerObject {
 	id: usize,
 	this: Shared<Weak<InnerObject>>,
 	map: Shared<dyn Mapping>,
-	env: Shared<Environment>
+	env: Shared<Environment>,
+	location: Shared<Option<Location>>
 }
 
 impl Object {
@@ -38,7 +39,8 @@ impl Object {
 			id: ID_COUNTER.fetch_add(1, Ordering::Relaxed),
 			map,
 			env,
-			this: Shared::new(Weak::new())
+			this: Shared::new(Weak::new()),
+			location: Shared::new(None)
 		}));
 
 		*obj.0.this.write() = Arc::downgrade(&obj.0);
@@ -63,6 +65,23 @@ impl Object {
 		&self.0.env
 	}
 
+	/// Where this object was parsed, if it was parsed at all
+	pub fn location(&self) -> Option<Location> {
+		self.0.location.read().clone()
+	}
+
+	pub fn set_location(&self, location: Location) {
+		*self.0.location.write() = Some(location);
+	}
+
+	/// Attaches this object's location to `err`, if it has one.
+	pub fn locate_err(&self, err: Error) -> Error {
+		match self.location() {
+			Some(location) => err.located(location),
+			None => err
+		}
+	}
+
 	pub fn evaluate(&self, parser: &Shared<Parser>) -> Result<Object> {
 		self.call_attr("__evaluate__", &[&parser.clone().into_object()])
 	}
@@ -90,7 +109,9 @@ impl Object {
 	}
 	
 	pub fn duplicate(&self) -> Object {
-		Object::new_with_env(self.0.map.duplicate(), self.0.env.clone())
+		let dup = Object::new_with_env(self.0.map.duplicate(), self.0.env.clone());
+		*dup.0.location.write() = self.location();
+		dup
 	}
 }
 
@@ -171,6 +192,13 @@ impl Mapping for Object {
 			}
 		}
 
+		// the attributes of an environment are its local variables, so `$closure.x = 1` works
+		if let Some(env) = self.downcast_env() {
+			if let Some(x) = env.read().locals().get(key) {
+				return Some(x)
+			}
+		}
+
 		if let Some(val) = self.0.map.read().get(key) {
 			Some(val)
 		} else if let Some(missing) = self.0.map.read().get(&"__missing__".into_object()) {
@@ -181,20 +209,43 @@ impl Mapping for Object {
 		}
 	}
 
-	#[inline]
 	fn set(&mut self, key: Object, val: Object) -> Option<Object> {
+		if let Some(env) = self.downcast_env() {
+			return env.write().set(key, val);
+		}
+
 		self.0.map.write().set(key, val)
 	}
 
-	#[inline]
 	fn del(&mut self, key: &Object) -> Option<Object> {
+		// like with `get`, locals come first, and the object's own attributes after
+		if let Some(env) = self.downcast_env() {
+			let mut locals = env.read().locals();
+			if locals.has(key) {
+				return locals.del(key);
+			}
+		}
+
 		self.0.map.write().del(key)
 	}
 
-	#[inline]
 	fn has(&self, key: &Object) -> bool {
+		if let Some(env) = self.downcast_env() {
+			if env.read().locals().has(key) {
+				return true;
+			}
+		}
+
 		self.0.map.read().has(key)
 	}
+
+	fn keys(&self) -> Vec<Object> {
+		let mut keys = self.downcast_env()
+			.map(|env| env.read().locals().keys())
+			.unwrap_or_default();
+		keys.extend(self.0.map.read().keys());
+		keys
+	}
 }
 
 // poetry is meaningless in philosophy
@@ -4,29 +4,43 @@ mod pristine;
 mod boolean;
 mod null;
 mod number;
+mod rational;
 mod text;
+mod interpolation;
 mod variable;
 mod rustfn;
 mod list;
 mod map;
+pub(crate) mod iter;
+mod range;
 mod oper;
 mod bound;
+pub(crate) mod error;
+mod format;
 pub mod block;
+mod params;
 
 pub use self::{
 	boolean::Boolean,
 	null::Null,
 	number::Number,
+	rational::Rational,
 	text::Text,
+	interpolation::{Interpolation, Segment},
 	variable::Variable,
 	rustfn::RustFn,
 	list::List,
 	map::Map,
+	iter::Iter,
+	range::Range,
 	oper::Oper,
 	block::Block,
-	bound::BoundObject
+	params::{Params, Param},
+	bound::BoundObject,
+	error::ErrorObject
 };
 pub(crate) use self::pristine::PRISTINE_MAP;
+pub(crate) use self::format::format;
 
 use crate::shared::Shared;
 use crate::object::Object;
@@ -43,16 +57,49 @@ pub(crate) enum Types {
 	Null,
 	Boolean(Boolean),
 	Number(Number),
+	Rational(Rational),
 	Text(Text),
+	Interpolation(Interpolation),
 	Variable(Variable),
 	RustFn(RustFn),
 	List(List),
 	Map(Map),
+	Iter(Iter),
+	Range(Range),
 	Oper(Oper),
 	Parser(Shared<crate::parse::Parser>),
 	Env(Shared<crate::env::Environment>),
 	Block(Block),
-	BoundObject(BoundObject)
+	BoundObject(BoundObject),
+	Error(ErrorObject)
+}
+
+/// Converts `obj` into an index via `@num`, failing if it isn't a whole number.
+pub(crate) fn to_index(obj: &Object, func: &'static str, position: usize) -> crate::Result<isize> {
+	obj.into_num()?.to_isize().ok_or_else(|| crate::Error::BadArgument {
+		func,
+		msg: "index isn't a whole number",
+		position,
+		obj: obj.clone()
+	})
+}
+
+/// Converts an index that may be negative (counting back from the end) into a position in
+/// something `len` long, if it's in bounds.
+pub(crate) fn normalize_index(index: isize, len: usize) -> Option<usize> {
+	let index = if index < 0 { index + len as isize } else { index };
+
+	if 0 <= index && (index as usize) < len {
+		Some(index as usize)
+	} else {
+		None
+	}
+}
+
+/// Like `normalize_index`, but for either end of a range, so out-of-bounds indices are clamped.
+pub(crate) fn normalize_bound(index: isize, len: usize) -> usize {
+	let index = if index < 0 { index + len as isize } else { index };
+	index.max(0).min(len as isize) as usize
 }
 
 pub(crate) trait Type : Into<Types> {
@@ -86,16 +133,21 @@ impl Display for Types {
 			Types::Null => Display::fmt(&Null, f),
 			Types::Boolean(ref bool) => Display::fmt(bool, f),
 			Types::Number(ref num) => Display::fmt(num, f),
+			Types::Rational(ref rational) => Display::fmt(rational, f),
 			Types::Text(ref text) => Display::fmt(text, f),
+			Types::Interpolation(ref interpolation) => Display::fmt(interpolation, f),
 			Types::Variable(ref var) => Display::fmt(var, f),
 			Types::RustFn(ref rustfn) => Display::fmt(rustfn, f),
 			Types::List(ref list) => Display::fmt(list, f),
 			Types::Map(ref map) => Display::fmt(map, f),
+			Types::Iter(ref iter) => Display::fmt(iter, f),
+			Types::Range(ref range) => Display::fmt(range, f),
 			Types::Oper(ref oper) => Display::fmt(oper, f),
 			Types::Parser(_) => write!(f, "<parser>"),
 			Types::Env(_) => write!(f, "<env>"),
 			Types::BoundObject(bound) => write!(f, "<bound>"),
 			Types::Block(ref block) => Display::fmt(block, f),
+			Types::Error(ref err) => Display::fmt(err, f),
 		}
 	}
 }
@@ -107,16 +159,21 @@ impl Debug for Types {
 			Types::Null => Debug::fmt(&Null, f),
 			Types::Boolean(ref bool) => Debug::fmt(bool, f),
 			Types::Number(ref num) => Debug::fmt(num, f),
+			Types::Rational(ref rational) => Debug::fmt(rational, f),
 			Types::Text(ref text) => Debug::fmt(text, f),
+			Types::Interpolation(ref interpolation) => Debug::fmt(interpolation, f),
 			Types::Variable(ref var) => Debug::fmt(var, f),
 			Types::RustFn(ref rustfn) => Debug::fmt(rustfn, f),
 			Types::List(ref list) => Debug::fmt(list, f),
 			Types::Map(ref map) => Debug::fmt(map, f),
+			Types::Iter(ref iter) => Debug::fmt(iter, f),
+			Types::Range(ref range) => Debug::fmt(range, f),
 			Types::Oper(ref oper) => Debug::fmt(oper, f),
 			Types::Parser(ref parser) => Debug::fmt(parser, f),
 			Types::Env(ref env) => Debug::fmt(env, f),
 			Types::BoundObject(ref bound) => Debug::fmt(bound, f),
 			Types::Block(ref block) => Debug::fmt(block, f),
+			Types::Error(ref err) => Debug::fmt(err, f),
 		}
 	}
 }
@@ -180,4 +237,31 @@ impl Mapping for TypedObject {
 	fn has(&self, key: &Object) -> bool {
 		self.map.has(key)
 	}
-}
\ No newline at end of file
+
+	#[inline]
+	fn keys(&self) -> Vec<Object> {
+		self.map.keys()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::{normalize_index, normalize_bound};
+
+	#[test]
+	fn indices() {
+		assert_eq!(normalize_index(0, 3), Some(0));
+		assert_eq!(normalize_index(-1, 3), Some(2));
+		assert_eq!(normalize_index(3, 3), None);
+		assert_eq!(normalize_index(-4, 3), None);
+		assert_eq!(normalize_index(0, 0), None);
+	}
+
+	#[test]
+	fn bounds() {
+		assert_eq!(normalize_bound(3, 3), 3);
+		assert_eq!(normalize_bound(-1, 3), 2);
+		assert_eq!(normalize_bound(10, 3), 3);
+		assert_eq!(normalize_bound(-10, 3), 0);
+	}
+}
@@ -27,6 +27,20 @@ basic_map! {
 		true.into_object()
 	}
 
+	// anything iterable can be used as a list
+	fn "@list" (@this) {
+		if !this.has_attr("@iter") {
+			return Err(ConversionFailure { func: "@list", obj: this.clone() });
+		}
+
+		crate::object::typed::iter::elements(this)?.into_object()
+	}
+
+	// how the object is shown when it's formatted with `{!r}`
+	fn "@repr" (@this) {
+		this.as_text_obj()?
+	}
+
 	fn "==" (@lhs, rhs) {
 		lhs.call_attr("===", &[rhs])?
 	}
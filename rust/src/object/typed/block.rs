@@ -1,18 +1,98 @@
-use crate::{Shared, Result};
-use crate::parse::Parser;
+use crate::{Shared, Result, Environment};
+use crate::parse::{Parser, Location};
+use crate::env::trace::{self, Frame};
 use crate::object::{TypedObject, Object};
+use super::params::Params;
 use std::fmt::{self, Debug, Display, Formatter};
 use lazy_static::lazy_static;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum Parens { Curly, Square, Round }
 
+/// A block of code, like `(...)`, `{...}` or `[...]`.
+///
+/// Square blocks are either list literals (`[1, 2, 3]`) or indexes (`x[0]`). Which one is decided
+/// when parsing: a `[` written directly after an expression (ie after an identifier, a literal or
+/// a closing paren, with no whitespace in between) is an index, and anything else is a literal.
+/// So `x[0]` indexes `x`, but `x [0]` pushes `x` and then the list `[0]`.
+///
+/// Curly blocks can start with a list of parameters, like `{ |a, b = 2| a + b }`, which are set as
+/// locals when the block is called. See [`Params`] for what they can be.
+///
+/// Blocks are closures: when parsed, they capture the environment they're in, and calling a curly
+/// block runs it in a new child of that environment. Since the environment is shared rather than
+/// copied, blocks see later changes to the variables they captured, and can change them through
+/// `$closure` (eg ``$closure.`count` = count + 1``).
 #[derive(Clone, PartialEq, Eq, Hash)]
-pub struct Block { parens: Parens, body: String }
+pub struct Block {
+	parens: Parens,
+	body: String,
+	location: Location,
+	index: bool,
+	params: Params,
+	closure: Option<Shared<Environment>>
+}
 
 impl Block {
 	pub fn new<T: Into<String>>(parens: Parens, body: T) -> Block {
-		Block { parens, body: body.into() }
+		Block::new_at(parens, body, Location::default())
+	}
+
+	/// Creates a block whose body starts at `location`, so errors in it point to the right place
+	pub fn new_at<T: Into<String>>(parens: Parens, body: T, location: Location) -> Block {
+		let mut body = body.into();
+		let mut location = location;
+
+		// only curly blocks take parameters, and parsing them interns their names, so other blocks
+		// (such as `[a | b]`) aren't parsed at all
+		let parsed = if parens == Parens::Curly { Params::parse(&body, &location) } else { None };
+		let params = match parsed {
+			Some((params, len)) => {
+				location = location.after(&body[..len]);
+				body.replace_range(..len, "");
+				params
+			},
+			None => Params::default()
+		};
+
+		Block { parens, body, location, index: false, params, closure: None }
+	}
+
+	/// Makes this block a closure over `env`
+	pub fn capture(self, env: Shared<Environment>) -> Block {
+		Block { closure: Some(env), ..self }
+	}
+
+	/// The environment this block captured, if it's captured one
+	pub fn closure(&self) -> Option<&Shared<Environment>> {
+		self.closure.as_ref()
+	}
+
+	/// Makes this square block index the object before it, rather than being a list literal
+	pub fn into_index(self) -> Block {
+		debug_assert_eq!(self.parens, Parens::Square, "only square blocks can be indexes");
+		Block { index: true, ..self }
+	}
+
+	pub fn is_index(&self) -> bool {
+		self.index
+	}
+
+	/// Runs the body in a child of the current environment, returning everything it left on
+	/// the stack. This is how the contents of list literals and indexes are evaluated.
+	pub fn evaluate_contents(&self) -> Result<Vec<Object>> {
+		let parser = Shared::new(Parser::from_str_at(self.body.clone(), self.location.clone()));
+		let env = Environment::execute(Environment::new(parser, Some(Environment::current()), None, None))?;
+		let stack = env.read().stack.read()._to_vec();
+		Ok(stack)
+	}
+
+	pub fn location(&self) -> &Location {
+		&self.location
+	}
+
+	pub fn params(&self) -> &Params {
+		&self.params
 	}
 }
 
@@ -40,7 +120,8 @@ impl Parens {
 impl Display for Block {
 	fn fmt(&self, f: &mut Formatter) -> fmt::Result {
 		match self.parens {
-			Parens::Curly => write!(f, "{{{}}}", self.body),
+			Parens::Curly if self.params.is_empty() => write!(f, "{{{}}}", self.body),
+			Parens::Curly => write!(f, "{{ {}{}}}", self.params, self.body),
 			Parens::Square => write!(f, "[{}]", self.body),
 			Parens::Round => write!(f, "({})", self.body),
 		}
@@ -75,18 +156,32 @@ impl_type! { for Block, downcast_fn=downcast_block;
 	}
 
 	fn "()" (@this) args {
-		use crate::{Environment, parse::Parser};
-
-		let (body, is_square) = this.downcast_block().map(|block| (block.body.clone(), block.parens == Parens::Square)).expect("<todo: error here>");
-		let parser = Shared::new(Parser::from_str(body));
-		let parent = Some(this.env().clone());
+		let Block { parens, body, location, params, closure, .. } = this.downcast_block().expect("<todo: error here>");
+		let name = trace::take_callee_name(this);
+		Frame::Block { location: location.clone(), name }.replace_current();
+
+		let parser = Shared::new(Parser::from_str_at(body, location));
+		// blocks made from rust, rather than parsed, fall back to where their object was made
+		let parent = closure.unwrap_or_else(|| this.env().clone());
+
+		// the arguments are still on the stack when there are parameters, so `@0` etc. keep working
+		let args = args.iter().skip(1).map(|x| (*x).clone()).collect::<Vec<_>>();
+		let stack = Some(Shared::new(crate::collections::List::new(args.clone())) as _);
+		let env = if parens == Parens::Curly {
+			Environment::new_closure(parser, parent, stack)
+		} else {
+			Environment::new(parser, Some(parent), None, stack)
+		};
+
+		if !params.is_empty() {
+			params.bind(name.unwrap_or("block"), &args, &env)?;
+		}
 
-		let stack = Some(Shared::new(crate::collections::List::new(args.iter().skip(1).map(|x| (*x).clone()).collect::<Vec<_>>())) as _);
-		let env = Environment::execute(Environment::new(parser, parent, None, stack))?;
+		let env = Environment::execute(env)?;
 		let x = env.read().stack.write().pop().ok_or_else(|| crate::err::Error::NothingToReturn)?;
 		x
 		// if is_square {
-		// 	use crate::{Environment, parse::Parser};
+		// 	use crate::parse::Parser;
 		// 	let env = Environment::execute(
 		// 		Environment::_new_default_with_stream_and_parent(Shared::new(parser), Some(this.env().clone()))
 		// 	)?;
@@ -97,9 +192,18 @@ impl_type! { for Block, downcast_fn=downcast_block;
 		// }
 	}
 
+	// a list of maps describing each parameter; see `Params::describe`
+	fn "params" (this) {
+		this.params().describe()
+	}
+
+	fn "closure" (this) {
+		this.closure().cloned().map(IntoObject::into_object).unwrap_or_else(Object::new_null)
+	}
+
 	fn "__evaluate__" (@this, parser) {
-		let parens = { this.downcast_block().expect("todo: error").parens };
-		match parens {
+		let block = this.downcast_block().expect("todo: error");
+		match block.parens {
 			Parens::Round => {
 				let contents = this.call_attr("()", &[])?;
 				// println!("{:?}", crate::Environment::current().read().stack);
@@ -110,7 +214,14 @@ impl_type! { for Block, downcast_fn=downcast_block;
 					contents
 				// }
 			},
-			Parens::Curly | Parens::Square => this.clone(),
+			Parens::Square if block.index => {
+				let indexed = crate::Environment::current().read().stack.write().pop()
+					.ok_or_else(|| crate::Error::MissingArgument { func: "[]", pos: 0, param: None })?;
+				let args = block.evaluate_contents()?;
+				indexed.call_attr("[]", &args.iter().collect::<Vec<_>>())?
+			},
+			Parens::Square => block.evaluate_contents()?.into_object(),
+			Parens::Curly => this.clone(),
 		}
 	}
 }
@@ -24,6 +24,9 @@ impl_type! { for BoundObject, downcast_fn=downcast_bound;
 				let mut callargs = args.to_owned();
 				assert!(callargs.len() >= 1, "callargs called without any args?");
 				callargs[0] = &bound.parent;
+				if let Some(key) = bound.key.downcast_var() {
+					crate::env::trace::name_callee(&func, key.into_inner());
+				}
 				func.call_attr("()", &callargs)?
 			} else {
 				Object::new_null()
@@ -0,0 +1,265 @@
+use crate::{Object, Error, Result, IntoObject};
+use crate::collections::{Mapping, ParentalMap};
+use crate::parse::Location;
+use crate::env::trace::Frame;
+use std::collections::HashMap;
+use std::fmt::{self, Debug, Display, Formatter};
+use std::sync::RwLock;
+use lazy_static::lazy_static;
+
+/// An error that Quest code can inspect, created when `try` catches something or by the `Error` builtin.
+///
+/// Every kind of error is an object, which is the parent map (`err.@parent`) of the errors of that
+/// kind. A kind's own parent is the kind it was derived from, and this ends at `Error`, whose parent
+/// holds the methods that every error has. So attributes added to a kind are given to all of its
+/// errors (and those of kinds derived from it), and `err.is(kind)` is true for `err`'s own kind
+/// and everything above it. New kinds are made with `ErrorKind(name, parent)`.
+#[derive(Clone, PartialEq, Debug)]
+pub struct ErrorObject {
+	kind: String,
+	message: String,
+	object: Option<Object>,
+	location: Option<Location>,
+	trace: Vec<Frame>
+}
+
+lazy_static! {
+	// every kind of error, by name
+	static ref KINDS: RwLock<HashMap<String, Object>> = RwLock::new(HashMap::new());
+}
+
+// the name kinds are looked up by
+fn name_key() -> Object {
+	"name".into_object()
+}
+
+fn parent_key() -> Object {
+	"@parent".into_object()
+}
+
+/// Returns the kind called `name`, creating it as a kind of `Error` if there isn't one yet.
+pub fn kind(name: &str) -> Object {
+	if let Some(kind) = KINDS.read().expect("error kinds poisoned").get(name) {
+		return kind.clone();
+	}
+
+	let parent = if name == "Error" {
+		// the parent of `Error` is the map with every error's methods
+		use super::Type;
+		ErrorObject::create_mapping().get(&parent_key()).expect("error map has no parent")
+	} else {
+		kind("Error")
+	};
+
+	derive_kind(name, &parent).expect("kind was just checked")
+}
+
+/// Creates a new kind of error called `name`, derived from `parent`. Returns `None` if there's
+/// already a kind with that name and a different parent.
+pub fn derive_kind(name: &str, parent: &Object) -> Option<Object> {
+	let mut kinds = KINDS.write().expect("error kinds poisoned");
+
+	if let Some(kind) = kinds.get(name) {
+		let same_parent = kind.get(&parent_key()).map(|old| old.ptr_eq(parent)).unwrap_or(false);
+		return if same_parent { Some(kind.clone()) } else { None };
+	}
+
+	let mut kind = Object::new(ParentalMap::new_with_parent(parent.clone()));
+	kind.set(name_key(), name.to_string().into_object());
+	kinds.insert(name.to_string(), kind.clone());
+	Some(kind)
+}
+
+/// The name of `kind`, if it's a kind of error
+pub fn kind_name(kind: &Object) -> Option<String> {
+	KINDS.read().expect("error kinds poisoned")
+		.iter()
+		.find(|(_, other)| other.ptr_eq(kind))
+		.map(|(name, _)| name.clone())
+}
+
+// converts `obj`, which is either a kind or its name, into the kind's name
+fn to_kind_name(obj: &Object) -> Result<String> {
+	match kind_name(obj) {
+		Some(name) => Ok(name),
+		None => Ok(obj.into_text()?.into_inner())
+	}
+}
+
+impl ErrorObject {
+	pub fn new<K: Into<String>, M: Into<String>>(kind: K, message: M, object: Option<Object>) -> ErrorObject {
+		ErrorObject {
+			kind: kind.into(),
+			message: message.into(),
+			object,
+			location: None,
+			trace: Vec::new()
+		}
+	}
+
+	/// Converts an error into something Quest can look at.
+	///
+	/// If the error was thrown with an error object, a copy of its data is returned, with the
+	/// location and trace filled in if it doesn't have any. Use `caught` to keep the object itself.
+	pub fn from_error(err: &Error) -> ErrorObject {
+		let mut error = match err.inner() {
+			Error::Thrown(obj) => obj.downcast_error().unwrap_or_else(||
+				ErrorObject::new("Error", obj.into_text().map(String::from).unwrap_or_else(|_| obj.to_string()), Some(obj.clone()))
+			),
+			Error::MissingKey { obj, .. }
+				| Error::ConversionFailure { obj, .. }
+				| Error::BadArgument { obj, .. } => ErrorObject::new(err.inner().name(), err.inner().to_string(), Some(obj.clone())),
+			other => ErrorObject::new(other.name(), other.to_string(), None)
+		};
+
+		if error.location.is_none() {
+			error.location = err.location().cloned();
+		}
+
+		if error.trace.is_empty() {
+			error.trace = err.trace().to_vec();
+		}
+
+		error
+	}
+
+	/// Like `from_error`, but returns the object that `try`'s handler is given. Error objects that
+	/// were thrown are given back themselves rather than copied, so attributes set on them are kept.
+	pub fn caught(err: &Error) -> Object {
+		let thrown = match err.inner() {
+			Error::Thrown(obj) if obj.is_error() => obj,
+			_ => return ErrorObject::from_error(err).objectify()
+		};
+
+		let mut map = thrown.map().write();
+		if let Some(super::Types::Error(error)) = map.downcast_mut::<super::TypedObject>().map(|typed| &mut typed.data) {
+			if error.location.is_none() {
+				error.location = err.location().cloned();
+			}
+
+			if error.trace.is_empty() {
+				error.trace = err.trace().to_vec();
+			}
+		}
+		drop(map);
+
+		thrown.clone()
+	}
+
+	pub fn kind(&self) -> &str {
+		&self.kind
+	}
+
+	/// Whether this is an error of the kind called `name`, or of a kind derived from it
+	pub fn is(&self, name: &str) -> bool {
+		let mut current = Some(kind(&self.kind));
+
+		while let Some(kind) = current {
+			match kind_name(&kind) {
+				Some(ref found) if found == name => return true,
+				Some(_) => current = kind.get(&parent_key()),
+				None => return false
+			}
+		}
+
+		false
+	}
+
+	/// Makes this into an object whose parent is its kind. Use this rather than `into_object`,
+	/// which leaves the parent as the map that every error shares.
+	pub fn objectify(self) -> Object {
+		let kind = kind(&self.kind);
+		let mut error = self.into_object();
+		error.set(parent_key(), kind);
+		error
+	}
+
+	pub fn message(&self) -> &str {
+		&self.message
+	}
+}
+
+impl Display for ErrorObject {
+	fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+		write!(f, "{}: {}", self.kind, self.message)
+	}
+}
+
+impl_typed_object!(ErrorObject, variant Error, new_error, downcast_error, is_error);
+
+impl_type! { for ErrorObject, downcast_fn=downcast_error;
+	// kinds inherit this too, so they're shown as their name
+	fn "@text" (@this) {
+		match this.downcast_error() {
+			Some(error) => error.to_string().into_object(),
+			None => to_kind_name(this)?.into_object()
+		}
+	}
+
+	fn "kind" (this) { this.kind.into_object() }
+	fn "message" (this) { this.message.into_object() }
+	fn "object" (this) { this.object.unwrap_or_else(Object::new_null) }
+
+	fn "location" (this) {
+		this.location.map(|location| location.to_string().into_object()).unwrap_or_else(Object::new_null)
+	}
+
+	// innermost call first, like the trace printed for uncaught errors
+	fn "trace" (this) {
+		this.trace.iter()
+			.rev()
+			.map(|frame| frame.to_string().into_object())
+			.collect::<Vec<_>>()
+			.into_object()
+	}
+
+	// `kind` is either a kind or its name
+	fn "is" (this, kind) {
+		this.is(&to_kind_name(kind)?).into_object()
+	}
+
+	fn "throw" (@this) {
+		return Err(Thrown(this.clone()))
+	}
+
+	// kinds are only equal to themselves
+	fn "==" (@this, rhs) {
+		match (this.downcast_error(), rhs.downcast_error()) {
+			(Some(lhs), Some(rhs)) => lhs == rhs,
+			_ => this.ptr_eq(rhs)
+		}.into_object()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn derived_kinds() {
+		let lookup = derive_kind("LookupError", &kind("Error")).unwrap();
+		let key = derive_kind("KeyError", &lookup).unwrap();
+
+		let error = ErrorObject::new("KeyError", "no such key", None);
+		assert!(error.is("KeyError") && error.is("LookupError") && error.is("Error"));
+		assert!(!ErrorObject::new("LookupError", "", None).is("KeyError"));
+
+		// kinds can't be redefined with a different parent
+		assert!(derive_kind("KeyError", &kind("Error")).is_none());
+		assert!(derive_kind("KeyError", &lookup).unwrap().ptr_eq(&key));
+	}
+
+	#[test]
+	fn attributes_are_inherited() {
+		let mut lookup = derive_kind("InheritedLookup", &kind("Error")).unwrap();
+		derive_kind("InheritedKey", &lookup).unwrap();
+		lookup.set("hint".into_object(), "check the key".to_string().into_object());
+
+		let error = ErrorObject::new("InheritedKey", "missing", None).objectify();
+		assert_eq!(error.get(&"hint".into_object()), Some("check the key".to_string().into_object()));
+		assert!(error.get(&"@parent".into_object()).unwrap().ptr_eq(&kind("InheritedKey")));
+
+		// the methods that every error has are still there
+		assert_eq!(error.call_attr("message", &[]).unwrap(), "missing".to_string().into_object());
+	}
+}
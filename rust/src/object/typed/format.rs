@@ -0,0 +1,337 @@
+//! The mini-language used by `Text::format` and `sprintf`.
+//!
+//! Placeholders look like `{field!conversion:spec}`, where every part is optional:
+//! - `field` is empty (the next positional argument), a number (that positional argument), or a
+//!   name, which is looked up in the last argument (which has to be a `Map`).
+//! - `conversion` is `r` to use `@repr` instead of `@text`.
+//! - `spec` is `[[fill]align][sign][#][0][width][.precision][type]`, where `align` is one of `<`,
+//!   `>` and `^`, `sign` is `+`, `-` or ` `, and `type` is one of `s`, `d`, `f`, `e`, `x`, `X`,
+//!   `o` and `b`. `#` adds `0x`/`0o`/`0b` prefixes, and `0` pads numbers with zeros.
+//!
+//! `{{` and `}}` are literal braces.
+use crate::{Object, IntoObject, Error, Result};
+use crate::object::typed::Number;
+use num_bigint::BigInt;
+use num_traits::Signed;
+
+// the largest width or precision allowed, so that a typo can't allocate gigabytes
+const MAX_WIDTH: usize = 1 << 16;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Align { Left, Right, Center }
+
+#[derive(Debug, Default, PartialEq, Eq)]
+struct Spec {
+	fill: Option<char>,
+	align: Option<Align>,
+	sign: Option<char>,
+	alternate: bool,
+	zero: bool,
+	width: usize,
+	precision: Option<usize>,
+	kind: Option<char>
+}
+
+impl Spec {
+	fn parse(spec: &str) -> Option<Spec> {
+		let chars = spec.chars().collect::<Vec<_>>();
+		let mut result = Spec::default();
+		let mut pos = 0;
+
+		let align = |chr: Option<&char>| match chr {
+			Some('<') => Some(Align::Left),
+			Some('>') => Some(Align::Right),
+			Some('^') => Some(Align::Center),
+			_ => None
+		};
+
+		if let Some(align) = align(chars.get(1)) {
+			result.fill = Some(chars[0]);
+			result.align = Some(align);
+			pos = 2;
+		} else if let Some(align) = align(chars.get(0)) {
+			result.align = Some(align);
+			pos = 1;
+		}
+
+		if let Some(&sign) = chars.get(pos).filter(|&&chr| chr == '+' || chr == '-' || chr == ' ') {
+			result.sign = Some(sign);
+			pos += 1;
+		}
+
+		if chars.get(pos) == Some(&'#') {
+			result.alternate = true;
+			pos += 1;
+		}
+
+		if chars.get(pos) == Some(&'0') {
+			result.zero = true;
+			pos += 1;
+		}
+
+		let read_number = |pos: &mut usize| {
+			let start = *pos;
+			while chars.get(*pos).map(|chr| chr.is_digit(10)).unwrap_or(false) {
+				*pos += 1;
+			}
+			chars[start..*pos].iter().collect::<String>().parse::<usize>().ok()
+		};
+
+		result.width = read_number(&mut pos).unwrap_or(0);
+
+		if chars.get(pos) == Some(&'.') {
+			pos += 1;
+			result.precision = Some(read_number(&mut pos)?);
+		}
+
+		if let Some(&kind) = chars.get(pos) {
+			if !"sdfexXob".contains(kind) {
+				return None;
+			}
+			result.kind = Some(kind);
+			pos += 1;
+		}
+
+		if pos == chars.len() {
+			Some(result)
+		} else {
+			None
+		}
+	}
+
+	// formats a number, returning its sign (and prefix) separately so zero padding can go between
+	fn number(&self, num: &Number) -> Option<(String, String)> {
+		let negative = *num < Number::default();
+		let mut prefix = match (negative, self.sign) {
+			(true, _) => "-".to_string(),
+			(false, Some('+')) => "+".to_string(),
+			(false, Some(' ')) => " ".to_string(),
+			(false, _) => String::new()
+		};
+
+		let mut integer = |radix_prefix: &str| -> Option<BigInt> {
+			let int = num.to_bigint()?;
+			if self.alternate {
+				prefix.push_str(radix_prefix);
+			}
+			Some(int.abs())
+		};
+
+		let digits = match self.kind {
+			Some('d') => integer("")?.to_string(),
+			Some('x') => format!("{:x}", integer("0x")?),
+			Some('X') => format!("{:X}", integer("0x")?),
+			Some('o') => format!("{:o}", integer("0o")?),
+			Some('b') => format!("{:b}", integer("0b")?),
+			Some('e') => format!("{:.*e}", self.precision.unwrap_or(6), num.to_f64().abs()),
+			Some('f') => format!("{:.*}", self.precision.unwrap_or(6), num.to_f64().abs()),
+			_ => match self.precision {
+				Some(precision) => format!("{:.*}", precision, num.to_f64().abs()),
+				None => num.abs().to_string()
+			}
+		};
+
+		Some((prefix, digits))
+	}
+
+	fn pad(&self, prefix: &str, body: &str, default: Align) -> String {
+		let len = prefix.chars().count() + body.chars().count();
+		let amount = self.width.saturating_sub(len);
+
+		if self.zero && self.align.is_none() {
+			return format!("{}{}{}", prefix, "0".repeat(amount), body);
+		}
+
+		let fill = self.fill.unwrap_or(' ').to_string();
+		let (left, right) = match self.align.unwrap_or(default) {
+			Align::Left => (0, amount),
+			Align::Right => (amount, 0),
+			Align::Center => (amount / 2, amount - amount / 2)
+		};
+
+		format!("{}{}{}{}", fill.repeat(left), prefix, body, fill.repeat(right))
+	}
+}
+
+fn bad_template(func: &'static str, msg: &'static str, template: &Object) -> Error {
+	Error::BadArgument { func, msg, position: 0, obj: template.clone() }
+}
+
+// formats a single value according to `conversion` and `spec`
+fn format_value(func: &'static str, value: &Object, repr: bool, spec: &Spec) -> Result<String> {
+	let is_number = value.is_num() && spec.kind != Some('s');
+
+	if is_number || spec.kind.map(|kind| kind != 's').unwrap_or(false) {
+		let num = value.into_num()?;
+		let (prefix, digits) = spec.number(&num).ok_or_else(|| Error::BadArgument {
+			func,
+			msg: "integer formats need whole numbers",
+			position: 1,
+			obj: value.clone()
+		})?;
+		return Ok(spec.pad(&prefix, &digits, Align::Right));
+	}
+
+	let text = if repr {
+		value.call_attr("@repr", &[])?.into_text()?.into_inner()
+	} else {
+		value.into_text()?.into_inner()
+	};
+
+	let text = match spec.precision {
+		Some(precision) => text.chars().take(precision).collect(),
+		None => text
+	};
+
+	Ok(spec.pad("", &text, Align::Left))
+}
+
+/// Formats `template` with `args`. `func` is used for errors, and `template` is the object
+/// the template came from.
+pub(crate) fn format(func: &'static str, template: &Object, args: &[&Object]) -> Result<String> {
+	let text = template.into_text()?.into_inner();
+	let mut chars = text.chars().peekable();
+	let mut result = String::with_capacity(text.len());
+	let mut next_positional = 0;
+
+	while let Some(chr) = chars.next() {
+		match chr {
+			'{' if chars.peek() == Some(&'{') => { chars.next(); result.push('{'); },
+			'}' if chars.peek() == Some(&'}') => { chars.next(); result.push('}'); },
+			'}' => return Err(bad_template(func, "unmatched `}` in format", template)),
+			'{' => {
+				let mut placeholder = String::new();
+				loop {
+					match chars.next() {
+						Some('}') => break,
+						Some(chr) => placeholder.push(chr),
+						None => return Err(bad_template(func, "unterminated `{` in format", template))
+					}
+				}
+
+				let (field, spec) = match placeholder.find(':') {
+					Some(idx) => (&placeholder[..idx], &placeholder[idx + 1..]),
+					None => (placeholder.as_str(), "")
+				};
+
+				let (field, repr) = match field.find('!') {
+					Some(idx) if &field[idx + 1..] == "r" => (&field[..idx], true),
+					Some(idx) if &field[idx + 1..] == "s" => (&field[..idx], false),
+					Some(_) => return Err(bad_template(func, "unknown conversion in format", template)),
+					None => (field, false)
+				};
+
+				let spec = Spec::parse(spec).ok_or_else(|| bad_template(func, "bad spec in format", template))?;
+				if spec.width > MAX_WIDTH || spec.precision.map(|precision| precision > MAX_WIDTH).unwrap_or(false) {
+					return Err(bad_template(func, "width or precision is too large in format", template));
+				}
+
+				let value = if field.is_empty() {
+					next_positional += 1;
+					args.get(next_positional - 1).map(|arg| (*arg).clone())
+						.ok_or_else(|| bad_template(func, "not enough arguments for format", template))?
+				} else if let Ok(index) = field.parse::<usize>() {
+					args.get(index).map(|arg| (*arg).clone())
+						.ok_or_else(|| bad_template(func, "positional placeholder out of range", template))?
+				} else {
+					let map = args.last()
+						.filter(|arg| arg.is_map())
+						.ok_or_else(|| bad_template(func, "named placeholders need a map as the last argument", template))?;
+					map.call_attr("fetch", &[&field.to_string().into_object()])?
+				};
+
+				result.push_str(&format_value(func, &value, repr, &spec)?);
+			},
+			other => result.push(other)
+		}
+	}
+
+	Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::{Spec, Align};
+	use crate::{Object, IntoObject};
+	use crate::object::typed::{Number, Map};
+
+	fn format(template: &str, args: &[Object]) -> crate::Result<String> {
+		let args = args.iter().collect::<Vec<_>>();
+		super::format("test", &template.to_string().into_object(), &args)
+	}
+
+	fn text(text: &str) -> Object {
+		text.to_string().into_object()
+	}
+
+	fn number(spec: &str, num: Number) -> String {
+		let spec = Spec::parse(spec).expect("bad spec");
+		let (prefix, digits) = spec.number(&num).expect("couldn't format number");
+		spec.pad(&prefix, &digits, Align::Right)
+	}
+
+	#[test]
+	fn parses_specs() {
+		assert_eq!(Spec::parse(""), Some(Spec::default()));
+		assert_eq!(Spec::parse("*^+#010.3f"), Some(Spec {
+			fill: Some('*'),
+			align: Some(Align::Center),
+			sign: Some('+'),
+			alternate: true,
+			zero: true,
+			width: 10,
+			precision: Some(3),
+			kind: Some('f')
+		}));
+		assert_eq!(Spec::parse("q"), None);
+		assert_eq!(Spec::parse("5.f"), None);
+	}
+
+	#[test]
+	fn formats_numbers() {
+		assert_eq!(number("", Number::from(42)), "42");
+		assert_eq!(number("5", Number::from(42)), "   42");
+		assert_eq!(number("<5", Number::from(42)), "42   ");
+		assert_eq!(number("05", Number::from(-42)), "-0042");
+		assert_eq!(number("+d", Number::from(42)), "+42");
+		assert_eq!(number(".2", Number::new(3.14159)), "3.14");
+		assert_eq!(number("8.3f", Number::new(-2.5)), "  -2.500");
+		assert_eq!(number("x", Number::from(255)), "ff");
+		assert_eq!(number("#06X", Number::from(255)), "0x00FF");
+		assert_eq!(number("#b", Number::from(-5)), "-0b101");
+	}
+
+	#[test]
+	fn integer_formats_need_whole_numbers() {
+		assert!(Spec::parse("d").unwrap().number(&Number::new(1.5)).is_none());
+	}
+
+	#[test]
+	fn formats_text() {
+		assert_eq!(format("{} and {}", &[text("a"), 1.into_object()]).unwrap(), "a and 1");
+		assert_eq!(format("{1}{0}{1}", &[text("a"), text("b")]).unwrap(), "bab");
+		assert_eq!(format("{!r} {}", &[text("a"), text("b")]).unwrap(), "\"a\" b");
+		assert_eq!(format("{{{}}}", &[text("a")]).unwrap(), "{a}");
+		assert_eq!(format("{:>3}|{:.2}", &[text("a"), text("abc")]).unwrap(), "  a|ab");
+
+		let names = Map::from_pairs(vec![(text("name"), text("quest"))]).into_object();
+		assert_eq!(format("hi {name:^7}!", &[names]).unwrap(), "hi  quest !");
+	}
+
+	#[test]
+	fn format_errors() {
+		let message = |template: &str, args: &[Object]| match format(template, args) {
+			Err(crate::Error::BadArgument { msg, .. }) => msg,
+			other => panic!("expected a bad argument, got {:?}", other)
+		};
+
+		assert_eq!(message("{} {}", &[text("a")]), "not enough arguments for format");
+		assert_eq!(message("{2}", &[text("a")]), "positional placeholder out of range");
+		assert_eq!(message("{!q}", &[text("a")]), "unknown conversion in format");
+		assert_eq!(message("{name}", &[text("a")]), "named placeholders need a map as the last argument");
+		assert_eq!(message("{", &[]), "unterminated `{` in format");
+		assert_eq!(message("}", &[]), "unmatched `}` in format");
+		assert_eq!(message("{:1000000000}", &[1.into_object()]), "width or precision is too large in format");
+		assert_eq!(message("{:.1000000000f}", &[1.into_object()]), "width or precision is too large in format");
+	}
+}
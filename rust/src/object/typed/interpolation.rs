@@ -0,0 +1,80 @@
+use crate::{Shared, Object, Result, Environment};
+use crate::parse::{Parser, Location};
+use std::fmt::{self, Debug, Display, Formatter};
+use lazy_static::lazy_static;
+
+/// A piece of an interpolated text literal
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Segment {
+	Text(String),
+	/// The code inside `${...}`; `start` is the byte offset of the code within the literal
+	Code { code: String, start: usize }
+}
+
+/// A text literal containing `${...}`, which becomes a `Text` each time it's evaluated.
+#[derive(Clone, PartialEq, Eq)]
+pub struct Interpolation {
+	source: String,
+	segments: Vec<Segment>
+}
+
+impl Interpolation {
+	/// `source` is the literal as it was written (including quotes), and is used for locations
+	pub fn new(source: String, segments: Vec<Segment>) -> Interpolation {
+		Interpolation { source, segments }
+	}
+
+	pub fn segments(&self) -> &[Segment] {
+		&self.segments
+	}
+
+	/// Runs each `${...}` in a child of the current environment and joins the results' `@text`s.
+	///
+	/// `location` is where the literal starts, so errors point into the right place.
+	pub fn evaluate(&self, location: Option<Location>) -> Result<String> {
+		let mut text = String::new();
+
+		for segment in self.segments.iter() {
+			match segment {
+				Segment::Text(literal) => text.push_str(literal),
+				Segment::Code { code, start } => {
+					let location = location.as_ref()
+						.map(|location| location.after(&self.source[..*start]))
+						.unwrap_or_default();
+
+					let parser = Shared::new(Parser::from_str_at(code.clone(), location));
+					let env = Environment::execute(Environment::new(parser, Some(Environment::current()), None, None))?;
+					let value = env.read().stack.write().pop();
+
+					// an empty `${}` is just nothing
+					if let Some(value) = value {
+						text.push_str(&value.into_text()?.into_inner());
+					}
+				}
+			}
+		}
+
+		Ok(text)
+	}
+}
+
+impl Display for Interpolation {
+	fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+		Display::fmt(&self.source, f)
+	}
+}
+
+impl Debug for Interpolation {
+	fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+		write!(f, "Interpolation({})", self.source)
+	}
+}
+
+impl_typed_object!(Interpolation, new_interpolation, downcast_interpolation, is_interpolation);
+
+impl_type! { for Interpolation, downcast_fn=downcast_interpolation;
+	fn "__evaluate__" (@this, _parser) {
+		let interpolation = this.downcast_interpolation().expect("`__evaluate__` called on a non-interpolation");
+		interpolation.evaluate(this.location())?.into_object()
+	}
+}
@@ -0,0 +1,117 @@
+//! The iteration protocol.
+//!
+//! An object is iterable if its `@iter` returns an iterator: something whose `next` returns each
+//! element in turn and then calls `stop()` (which raises `Error::StopIteration`) once there are
+//! none left. Lists, maps and texts return an `Iter` from `@iter`, but user objects can define
+//! both methods themselves. The default `@list` collects an object's elements, so anything
+//! iterable can be passed where a list is expected.
+use crate::{Shared, Object, Error, Result};
+use std::fmt::{self, Debug, Display, Formatter};
+use lazy_static::lazy_static;
+
+/// A builtin iterator, which can be shared between objects; stepping one steps every copy.
+#[derive(Clone)]
+pub struct Iter(Shared<Box<dyn Iterator<Item=Object> + Send + Sync>>);
+
+impl Iter {
+	pub fn new<I: Iterator<Item=Object> + Send + Sync + 'static>(iter: I) -> Iter {
+		Iter(Shared::new(Box::new(iter)))
+	}
+}
+
+impl Iterator for Iter {
+	type Item = Object;
+
+	fn next(&mut self) -> Option<Object> {
+		self.0.write().next()
+	}
+}
+
+impl Eq for Iter {}
+impl PartialEq for Iter {
+	fn eq(&self, other: &Iter) -> bool {
+		self.0.ptr_eq(&other.0)
+	}
+}
+
+impl Display for Iter {
+	fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+		write!(f, "<iter>")
+	}
+}
+
+impl Debug for Iter {
+	fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+		write!(f, "Iter")
+	}
+}
+
+/// Steps through any iterable object using `@iter` and `next`.
+pub struct Elements(Object);
+
+impl Elements {
+	pub fn new(iterable: &Object) -> Result<Elements> {
+		Ok(Elements(iterable.call_attr("@iter", &[])?))
+	}
+}
+
+impl Iterator for Elements {
+	type Item = Result<Object>;
+
+	fn next(&mut self) -> Option<Result<Object>> {
+		// builtin iterators don't need to go through `next`
+		if let Some(mut iter) = self.0.downcast_iter() {
+			return iter.next().map(Ok);
+		}
+
+		match self.0.call_attr("next", &[]) {
+			Err(Error::StopIteration) => None,
+			other => Some(other)
+		}
+	}
+}
+
+/// Collects all the elements of `iterable`, stopping at the first error.
+pub fn elements(iterable: &Object) -> Result<Vec<Object>> {
+	Elements::new(iterable)?.collect()
+}
+
+impl_typed_object!(Iter, new_iter, downcast_iter, is_iter);
+
+impl_type! { for Iter, downcast_fn=downcast_iter;
+	fn "@iter" (@this) {
+		this.clone()
+	}
+
+	fn "next" (this) {
+		let mut iter = this;
+		iter.next().ok_or(StopIteration)?
+	}
+
+	// the remaining elements
+	fn "@list" (this) {
+		this.collect::<Vec<_>>().into_object()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::IntoObject;
+
+	#[test]
+	fn copies_share_state() {
+		let mut iter = Iter::new(vec![1.into_object(), 2.into_object()].into_iter());
+		let mut copy = iter.clone();
+		assert_eq!(iter.next(), Some(1.into_object()));
+		assert_eq!(copy.next(), Some(2.into_object()));
+		assert_eq!(iter.next(), None);
+	}
+
+	#[test]
+	fn builtin_elements() {
+		let list = vec![1.into_object(), 2.into_object()];
+		assert_eq!(elements(&list.clone().into_object()).unwrap(), list);
+		assert_eq!(elements(&"ab".to_string().into_object()).unwrap(), vec!["a".to_string().into_object(), "b".to_string().into_object()]);
+	}
+}
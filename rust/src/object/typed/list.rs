@@ -1,5 +1,7 @@
-use crate::{Object, Shared, collections::{self, Listing}};
+use crate::{Object, Shared, collections::{self, Collection, Listing}};
 use std::fmt::{self, Debug, Display, Formatter};
+use std::ops::Range;
+use std::cmp::Ordering;
 use lazy_static::lazy_static;
 
 #[derive(Clone, Debug)]
@@ -8,7 +10,7 @@ pub struct List(Shared<dyn Listing>);
 impl Eq for List {}
 impl PartialEq for List {
 	fn eq(&self, other: &List) -> bool {
-		self.0.read()._to_vec() == other.0.read()._to_vec()		
+		self.0.read().iter().eq(other.0.read().iter())
 	}
 }
 
@@ -21,17 +23,100 @@ impl List {
 	pub fn into_inner(self) -> Vec<Object> {
 		self.0.read()._to_vec()
 	}
+
+	// the elements from `start` up to `stop`, which are clamped like `normalize_bound`
+	fn range(&self, start: isize, stop: isize) -> Range<usize> {
+		let len = self.0.len();
+		let start = super::normalize_bound(start, len);
+		start..super::normalize_bound(stop, len).max(start)
+	}
+
+	// the part of the list that a range of indices covers, with negative bounds counting back
+	// from the end. only ranges that step by one cover a contiguous part.
+	fn slice(&self, range: &super::Range) -> Option<Range<usize>> {
+		if range.step() != 1 {
+			return None;
+		}
+
+		let stop = match (range.is_inclusive(), range.stop()) {
+			(false, stop) => stop,
+			(true, -1) => self.0.len() as isize,
+			(true, stop) => stop.saturating_add(1)
+		};
+
+		Some(self.range(range.start(), stop))
+	}
+
+	// replaces the elements in `range` with `replacement`
+	fn splice(&self, range: Range<usize>, replacement: Vec<Object>) {
+		self.0.write().splice(range, replacement);
+	}
+
+	// removes and returns the elements in `range`
+	fn drain(&self, range: Range<usize>) -> Vec<Object> {
+		self.0.write().splice(range, Vec::new())
+	}
+}
+
+// calls `func`, treating a block that doesn't return anything as returning `null`
+fn call(func: &Object, args: &[&Object]) -> crate::Result<Object> {
+	match func.call_attr("()", args) {
+		Err(crate::Error::NothingToReturn) => Ok(Object::new_null()),
+		other => other
+	}
+}
+
+fn truthy(func: &Object, args: &[&Object]) -> crate::Result<bool> {
+	Ok(call(func, args)?.into_bool()?.into_inner())
+}
+
+// compares with `<=>`; things that can't be ordered (like `NaN`) are considered equal
+fn compare(lhs: &Object, rhs: &Object) -> crate::Result<Ordering> {
+	let cmp = lhs.call_attr("<=>", &[rhs])?;
+	if cmp.is_null() {
+		return Ok(Ordering::Equal);
+	}
+
+	Ok(cmp.into_num()?.partial_cmp(&0.into()).unwrap_or(Ordering::Equal))
+}
+
+// sorts by `key`'s results, stopping at the first error
+fn sort_by_key(elements: &mut Vec<Object>, key: impl Fn(&Object) -> crate::Result<Object>) -> crate::Result<()> {
+	let keys = elements.iter().map(|ele| key(ele)).collect::<crate::Result<Vec<_>>>()?;
+	let mut keyed = keys.into_iter().zip(elements.drain(..)).collect::<Vec<_>>();
+	let mut err = None;
+
+	// `sort_by` is stable, so equal elements keep their order
+	keyed.sort_by(|(lhs, _), (rhs, _)| compare(lhs, rhs).unwrap_or_else(|e| {
+		err.get_or_insert(e);
+		Ordering::Equal
+	}));
+
+	if let Some(err) = err {
+		return Err(err);
+	}
+
+	elements.extend(keyed.into_iter().map(|(_, ele)| ele));
+	Ok(())
+}
+
+fn flatten_into(elements: Vec<Object>, flat: &mut Vec<Object>) {
+	for ele in elements {
+		match ele.downcast_list() {
+			Some(list) => flatten_into(list.into_inner(), flat),
+			None => flat.push(ele)
+		}
+	}
 }
 
 impl Display for List {
 	fn fmt(&self, f: &mut Formatter) -> fmt::Result {
 		write!(f, "[")?;
-		if !self.0.read().is_empty() {
-			let mut iter = self.clone().into_inner().into_iter();
-			write!(f, "{}", iter.next().unwrap())?;
-			for obj in iter {
-				write!(f, ", {}", obj)?;
+		for (i, obj) in self.0.read().iter().enumerate() {
+			if i != 0 {
+				write!(f, ", ")?;
 			}
+			write!(f, "{}", obj)?;
 		}
 		write!(f, "]")
 	}
@@ -72,6 +157,17 @@ impl_type! { for List, downcast_fn=downcast_list;
 		this.into_object()
 	}
 
+	// steps through the list as it currently is, so elements added while iterating are included
+	fn "@iter" (this) {
+		let list = this.0;
+		let mut index = 0;
+		Iter::new(std::iter::from_fn(move || {
+			let ele = list.read().get(index);
+			index += 1;
+			ele
+		})).into_object()
+	}
+
 	fn "@bool" (this) {
 		(!this.0.is_empty()).into_object()
 	}
@@ -97,7 +193,7 @@ impl_type! { for List, downcast_fn=downcast_list;
 	}
 
 	fn "*" (this, rhs) {
-		let lim = *rhs.into_num()?.into_integer().as_ref() as isize;
+		let lim = isize::from(rhs.into_num()?.into_integer());
 		if lim < 0 {
 			return Ok("".to_string().into_object());
 		}
@@ -114,10 +210,298 @@ impl_type! { for List, downcast_fn=downcast_list;
 		this.0.len().into_object()
 	}
 
-	fn "[]" (_this, _index) { todo!() }
-	fn "[]=" (_this, _index, _val) { todo!() }
-	fn "[]~" (_this, _index) { todo!() }
-	fn "[]?" (_this, _index) { todo!() }
+	// `list[i]` is the `i`th element, and `list[start, stop]` is the elements from `start` up to
+	// (but not including) `stop`. negative indices count back from the end. `list[range]` is the
+	// elements at each of the range's indices.
+	fn "[]" (this, index) args {
+		if let Some(range) = index.downcast_range() {
+			let elements = this.into_inner();
+			return Ok(match this.slice(&range) {
+				Some(slice) => elements[slice].to_vec(),
+				// only the indices that are in bounds (counting from either end) are looked at
+				None => range.within(-(elements.len() as isize), elements.len() as isize).iter()
+					.filter_map(|index| normalize_index(index, elements.len()))
+					.map(|index| elements[index].clone())
+					.collect()
+			}.into_object());
+		}
+
+		let start = to_index(index, function!(), 1)?;
+
+		// the number of arguments is checked instead of whether `stop` is null, so that a null
+		// `stop` is an error rather than silently ignored
+		if let Some(stop) = args.get(2) {
+			let range = this.range(start, to_index(stop, function!(), 2)?);
+			this.into_inner()[range].to_vec().into_object()
+		} else {
+			let list = this.0.read();
+			normalize_index(start, list.len())
+				.and_then(|index| list.get(index))
+				.unwrap_or_else(Object::new_null)
+		}
+	}
+
+	// `list[i] = val` replaces the `i`th element (or appends, if `i` is the length), and
+	// `list[start, stop] = other` replaces those elements with `other`'s. returns what was assigned.
+	// since `this` shares its elements with the original, this changes the original too.
+	fn "[]=" (this, index, stop_or_val) args {
+		if let Some(range) = index.downcast_range() {
+			let slice = this.slice(&range).ok_or_else(|| BadArgument {
+				func: function!(),
+				msg: "only ranges with a step of one can be assigned to",
+				position: 1,
+				obj: index.clone()
+			})?;
+			this.splice(slice, stop_or_val.into_list()?.into_inner());
+			return Ok(stop_or_val.clone());
+		}
+
+		let start = to_index(index, function!(), 1)?;
+
+		if let Some(val) = args.get(3) {
+			let range = this.range(start, to_index(stop_or_val, function!(), 2)?);
+			this.splice(range, val.into_list()?.into_inner());
+			(*val).clone()
+		} else {
+			let mut list = this.0.write();
+			let len = list.len();
+
+			match normalize_index(start, len) {
+				Some(index) => { list.set(index, stop_or_val.clone()); },
+				None if start == len as isize => list.push(stop_or_val.clone()),
+				None => return Err(BadArgument {
+					func: function!(),
+					msg: "index out of bounds",
+					position: 1,
+					obj: index.clone()
+				})
+			}
+
+			stop_or_val.clone()
+		}
+	}
+
+	// like `[]`, except the elements are removed
+	fn "[]~" (this, index) args {
+		if let Some(range) = index.downcast_range() {
+			let slice = this.slice(&range).ok_or_else(|| BadArgument {
+				func: function!(),
+				msg: "only ranges with a step of one can be removed",
+				position: 1,
+				obj: index.clone()
+			})?;
+			return Ok(this.drain(slice).into_object());
+		}
+
+		let start = to_index(index, function!(), 1)?;
+
+		if let Some(stop) = args.get(2) {
+			let range = this.range(start, to_index(stop, function!(), 2)?);
+			this.drain(range).into_object()
+		} else {
+			let mut list = this.0.write();
+			let len = list.len();
+			normalize_index(start, len)
+				.and_then(|index| list.remove(index))
+				.unwrap_or_else(Object::new_null)
+		}
+	}
+
+	// whether any element is `==` to `val`
+	fn "[]?" (this, val) {
+		for ele in this.into_inner() {
+			if ele.call_attr("==", &[val])?.into_bool()?.into_inner() {
+				return Ok(true.into_object());
+			}
+		}
+
+		false.into_object()
+	}
+
+	// callbacks can be anything callable; blocks that don't return anything return `null`
+	fn "each" (this, func) {
+		for ele in this.clone().into_inner() {
+			call(func, &[&ele])?;
+		}
+		this.into_object()
+	}
+
+	fn "map" (this, func) {
+		this.into_inner()
+			.iter()
+			.map(|ele| call(func, &[ele]))
+			.collect::<Result<Vec<_>>>()?
+			.into_object()
+	}
+
+	fn "filter" (this, func) {
+		let mut kept = Vec::new();
+		for ele in this.into_inner() {
+			if truthy(func, &[&ele])? {
+				kept.push(ele);
+			}
+		}
+		kept.into_object()
+	}
+
+	// without `init`, the first element is used and an empty list reduces to `null`
+	fn "reduce" (this, func; init=Object::new_null()) {
+		let mut elements = this.into_inner().into_iter();
+		let mut acc = if init.is_null() {
+			match elements.next() {
+				Some(first) => first,
+				None => return Ok(Object::new_null())
+			}
+		} else {
+			init
+		};
+
+		for ele in elements {
+			acc = call(func, &[&acc, &ele])?;
+		}
+
+		acc
+	}
+
+	fn "fold" (this, init, func) {
+		let mut acc = init.clone();
+		for ele in this.into_inner() {
+			acc = call(func, &[&acc, &ele])?;
+		}
+		acc
+	}
+
+	fn "any" (this, func) {
+		for ele in this.into_inner() {
+			if truthy(func, &[&ele])? {
+				return Ok(true.into_object());
+			}
+		}
+		false.into_object()
+	}
+
+	fn "all" (this, func) {
+		for ele in this.into_inner() {
+			if !truthy(func, &[&ele])? {
+				return Ok(false.into_object());
+			}
+		}
+		true.into_object()
+	}
+
+	fn "find" (this, func) {
+		for ele in this.into_inner() {
+			if truthy(func, &[&ele])? {
+				return Ok(ele);
+			}
+		}
+		Object::new_null()
+	}
+
+	fn "index_of" (this, val) {
+		for (index, ele) in this.into_inner().into_iter().enumerate() {
+			if ele.call_attr("==", &[val])?.into_bool()?.into_inner() {
+				return Ok(index.into_object());
+			}
+		}
+		Object::new_null()
+	}
+
+	// elements are compared with `<=>`
+	fn "sort" (this) {
+		let mut elements = this.into_inner();
+		sort_by_key(&mut elements, |ele| Ok(ele.clone()))?;
+		elements.into_object()
+	}
+
+	fn "sort_by" (this, func) {
+		let mut elements = this.into_inner();
+		sort_by_key(&mut elements, |ele| call(func, &[ele]))?;
+		elements.into_object()
+	}
+
+	fn "reverse" (this) {
+		this.into_inner().into_iter().rev().collect::<Vec<_>>().into_object()
+	}
+
+	// stops at the end of the shorter list
+	fn "zip" (this, rhs) {
+		this.into_inner()
+			.into_iter()
+			.zip(rhs.into_list()?.into_inner())
+			.map(|(lhs, rhs)| vec![lhs, rhs].into_object())
+			.collect::<Vec<_>>()
+			.into_object()
+	}
+
+	fn "enumerate" (this) {
+		this.into_inner()
+			.into_iter()
+			.enumerate()
+			.map(|(index, ele)| vec![index.into_object(), ele].into_object())
+			.collect::<Vec<_>>()
+			.into_object()
+	}
+
+	fn "flatten" (this) {
+		let mut flat = Vec::new();
+		flatten_into(this.into_inner(), &mut flat);
+		flat.into_object()
+	}
+
+	// keeps the first of each group of `==` elements
+	fn "uniq" (this) {
+		let mut uniq: Vec<Object> = Vec::new();
+		'outer: for ele in this.into_inner() {
+			for seen in uniq.iter() {
+				if seen.call_attr("==", &[&ele])?.into_bool()?.into_inner() {
+					continue 'outer;
+				}
+			}
+			uniq.push(ele);
+		}
+		uniq.into_object()
+	}
+
+	// the rest of these change the list itself
+	fn "push" (this) args {
+		for ele in args.iter().skip(1) {
+			this.0.write().push((*ele).clone());
+		}
+		this.into_object()
+	}
+
+	fn "pop" (this) {
+		this.0.write().pop().unwrap_or_else(Object::new_null)
+	}
+
+	fn "shift" (this) {
+		this.0.write().remove(0).unwrap_or_else(Object::new_null)
+	}
+
+	fn "unshift" (this, val) {
+		this.0.write().insert(0, val.clone());
+		this.into_object()
+	}
+
+	fn "insert" (this, index, val) {
+		let len = this.0.len();
+		let position = to_index(index, function!(), 1)?;
+		// unlike `[]`, the length itself is a valid place to insert
+		let position = if position == len as isize { Some(len) } else { normalize_index(position, len) };
+
+		match position {
+			Some(position) => this.0.write().insert(position, val.clone()),
+			None => return Err(BadArgument {
+				func: function!(),
+				msg: "index out of bounds",
+				position: 1,
+				obj: index.clone()
+			})
+		}
+
+		this.into_object()
+	}
 
 	// since `this` is actually a clone, this works
 	fn "union" (this, rhs) {
@@ -150,4 +534,46 @@ impl_type! { for List, downcast_fn=downcast_list;
 		    .collect::<Vec<_>>()
 		    .into_object()
 	}
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+	use crate::{Object, IntoObject};
+
+	fn texts(texts: &[&str]) -> Object {
+		texts.iter().map(|text| text.to_string().into_object()).collect::<Vec<_>>().into_object()
+	}
+
+	fn nums(nums: &[i64]) -> Object {
+		nums.iter().map(|&num| num.into_object()).collect::<Vec<_>>().into_object()
+	}
+
+	#[test]
+	fn sorts_texts() {
+		let list = texts(&["b", "c", "a", "ab"]);
+		assert_eq!(list.call_attr("sort", &[]).unwrap(), texts(&["a", "ab", "b", "c"]));
+	}
+
+	#[test]
+	fn ranges_are_replaced_and_removed() {
+		let list = nums(&[1, 2, 3, 4, 5]);
+		list.call_attr("[]=", &[&1.into_object(), &3.into_object(), &nums(&[9])]).unwrap();
+		assert_eq!(list, nums(&[1, 9, 4, 5]));
+
+		assert_eq!(list.call_attr("[]~", &[&(-2).into_object(), &4.into_object()]).unwrap(), nums(&[4, 5]));
+		assert_eq!(list, nums(&[1, 9]));
+	}
+
+	#[test]
+	fn null_is_a_value_not_a_missing_argument() {
+		let list = nums(&[1, 2, 3]);
+		let null = Object::new_null();
+
+		// `list[0, 2] = null` assigns to a range, which null can't be
+		assert!(list.call_attr("[]=", &[&0.into_object(), &2.into_object(), &null]).is_err());
+		assert!(list.call_attr("[]~", &[&0.into_object(), &null]).is_err());
+		assert_eq!(list, nums(&[1, 2, 3]));
+
+		list.call_attr("[]=", &[&0.into_object(), &null]).unwrap();
+		assert!(list.call_attr("[]", &[&0.into_object()]).unwrap().is_null());
+	}
+}
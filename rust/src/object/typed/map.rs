@@ -1,5 +1,5 @@
-use crate::Shared;
-use crate::collections::Mapping;
+use crate::{Shared, Object};
+use crate::collections::{self, Collection, Mapping};
 use std::fmt::{self, Debug, Display, Formatter};
 use lazy_static::lazy_static;
 
@@ -14,29 +14,49 @@ impl Map {
 	pub fn into_inner(self) -> Shared<dyn Mapping> {
 		self.0
 	}
+
+	/// Creates a new map containing `pairs`; later pairs overwrite earlier ones with the same key
+	pub fn from_pairs(pairs: Vec<(Object, Object)>) -> Map {
+		let mut map = collections::Map::empty();
+		for (key, val) in pairs {
+			map.set(key, val);
+		}
+		Map::new(Shared::new(map) as _)
+	}
+
+	/// The key-value pairs in this map
+	pub fn pairs(&self) -> Vec<(Object, Object)> {
+		self.0.pairs()
+	}
+
+	// the pairs of `self` for which `keep` returns true
+	fn filter(&self, mut keep: impl FnMut(&Object) -> bool) -> Map {
+		Map::from_pairs(self.pairs().into_iter().filter(|(key, _)| keep(key)).collect())
+	}
 }
 
 impl Display for Map {
-	fn fmt(&self, _f: &mut Formatter) -> fmt::Result {
-		unimplemented!("TODO: display for Map");
-		/*
+	fn fmt(&self, f: &mut Formatter) -> fmt::Result {
 		write!(f, "{{")?;
-		if !self.0.is_empty() {
-			let mut iter = self.0.iter();
-			iter.0;
-			write!(f, "{}", iter.next().unwrap())?;
-			for obj in iter {
-				write!(f, ", {}", obj)?;
+		for (i, (key, val)) in self.pairs().into_iter().enumerate() {
+			if i != 0 {
+				write!(f, ", ")?;
 			}
+			write!(f, "{}: {}", key, val)?;
 		}
-		write!(f, "}}")*/
+		write!(f, "}}")
 	}
 }
 
 impl Eq for Map {}
 impl PartialEq for Map {
-	fn eq(&self, _other: &Map) -> bool {
-		unimplemented!("TODO: equal for Map")
+	fn eq(&self, other: &Map) -> bool {
+		if self.0.ptr_eq(&other.0) {
+			return true;
+		}
+
+		self.0.len() == other.0.len() && self.pairs().into_iter().all(|(key, val)|
+			other.0.get(&key).map(|other_val| other_val == val).unwrap_or(false))
 	}
 }
 
@@ -57,7 +77,24 @@ impl_type! { for Map, downcast_fn=downcast_map;
 		this.into_object()
 	}
 
-	fn "@list" (_this) { todo!(); }
+	// a list of `[key, value]` pairs
+	fn "@list" (this) {
+		this.pairs()
+			.into_iter()
+			.map(|(key, val)| vec![key, val].into_object())
+			.collect::<Vec<_>>()
+			.into_object()
+	}
+
+	// iterates over `[key, value]` pairs, like `@list`
+	fn "@iter" (this) {
+		Iter::new(this.pairs().into_iter().map(|(key, val)| vec![key, val].into_object())).into_object()
+	}
+
+	fn "@text" (this) {
+		this.to_string().into_object()
+	}
+
 	fn "@bool" (this) {
 		(!this.0.is_empty()).into_object()
 	}
@@ -70,12 +107,66 @@ impl_type! { for Map, downcast_fn=downcast_map;
 		this.0.len().into_object()
 	}
 
+	fn "keys" (this) {
+		this.0.keys().into_object()
+	}
 
-	fn "+" (_this, _rhs) { todo!("iterable for mapping is needed") }
-	fn "-" (_this, _rhs) { todo!("iterable for mapping is needed") }
-	fn "union" (_this, _rhs) { todo!("iterable for mapping is needed") }
-	fn "intersect" (_this, _rhs) { todo!("iterable for mapping is needed") }
-	fn "symmetric_difference" (_this, _rhs) { todo!("iterable for mapping is needed") }
+	fn "values" (this) {
+		this.pairs().into_iter().map(|(_, val)| val).collect::<Vec<_>>().into_object()
+	}
+
+	fn "items" (@this) {
+		this.call_attr("@list", &[])?
+	}
+
+	fn "get" (this, key; default=Object::new_null()) {
+		this.0.get(key).unwrap_or(default)
+	}
+
+	// a new map with the pairs from both, where `rhs`'s values win
+	fn "merge" (this, rhs) {
+		let mut pairs = this.pairs();
+		pairs.extend(rhs.into_map()?.pairs());
+		Map::from_pairs(pairs).into_object()
+	}
+
+	// like `merge`, but changes this map instead of making a new one
+	fn "update" (@this, rhs) {
+		let pairs = rhs.into_map()?.pairs();
+		let mut inner = this.into_map()?.0;
+		for (key, val) in pairs {
+			inner.set(key, val);
+		}
+		this.clone()
+	}
+
+	fn "+" (@this, rhs) {
+		this.call_attr("merge", &[rhs])?
+	}
+
+	// the pairs whose keys aren't in `rhs`
+	fn "-" (this, rhs) {
+		let rhs = rhs.into_map()?;
+		this.filter(|key| !rhs.0.has(key)).into_object()
+	}
+
+	fn "union" (@this, rhs) {
+		this.call_attr("merge", &[rhs])?
+	}
+
+	// the pairs whose keys are also in `rhs`
+	fn "intersect" (this, rhs) {
+		let rhs = rhs.into_map()?;
+		this.filter(|key| rhs.0.has(key)).into_object()
+	}
+
+	// the pairs whose keys are in exactly one of the maps
+	fn "symmetric_difference" (this, rhs) {
+		let rhs = rhs.into_map()?;
+		let mut pairs = this.filter(|key| !rhs.0.has(key)).pairs();
+		pairs.extend(rhs.filter(|key| !this.0.has(key)).pairs());
+		Map::from_pairs(pairs).into_object()
+	}
 
 	fn "fetch" (@this, key) {
 		this.into_map()?.0.get(key).ok_or_else(|| MissingKey {
@@ -105,4 +196,27 @@ impl_type! { for Map, downcast_fn=downcast_map;
 	fn "[]?" (this, key) {
 		this.0.has(key).into_object()
 	}
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+	use super::Map;
+	use crate::IntoObject;
+
+	fn map(pairs: &[(&str, i64)]) -> Map {
+		Map::from_pairs(pairs.iter().map(|&(key, val)| (key.to_string().into_object(), val.into_object())).collect())
+	}
+
+	#[test]
+	fn display() {
+		assert_eq!(map(&[]).to_string(), "{}");
+		assert_eq!(map(&[("a", 1), ("b", 2)]).to_string(), "{a: 1, b: 2}");
+		assert_eq!(map(&[("a", 1), ("a", 2)]).to_string(), "{a: 2}");
+	}
+
+	#[test]
+	fn equality() {
+		assert_eq!(map(&[("a", 1), ("b", 2)]), map(&[("b", 2), ("a", 1)]));
+		assert_ne!(map(&[("a", 1)]), map(&[("a", 2)]));
+		assert_ne!(map(&[("a", 1)]), map(&[("a", 1), ("b", 2)]));
+	}
+}
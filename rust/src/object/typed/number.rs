@@ -1,54 +1,258 @@
 use crate::object::{Object, IntoObject};
+use crate::object::typed::Rational;
 use std::fmt::{self, Debug, Display, Formatter};
+use std::cmp::Ordering;
+use std::convert::TryFrom;
+use std::ops::{Add, Sub, Mul, Div, Rem, Neg};
+use num_bigint::BigInt;
+use num_traits::{Zero, Signed, ToPrimitive, FromPrimitive};
+use num_integer::Integer;
 use lazy_static::lazy_static;
 
-#[derive(Clone, Copy, PartialEq, Default)]
-pub struct Number(f64);
+/// The most bits an integer made by `**` or `<<` can have, so that huge results are errors
+/// instead of using up all the memory.
+pub const MAX_BITS: u64 = 1 << 24;
+
+/// Whether `base ** exp` is sure to have more than `MAX_BITS` bits
+pub(crate) fn pow_is_too_large(base: &BigInt, exp: usize) -> bool {
+	// a number with `bits` bits is at least `2 ** (bits - 1)`, so its powers can't be any smaller
+	let bits = base.bits() as u64;
+	bits.saturating_sub(1).saturating_mul(exp as u64).saturating_add(1) > MAX_BITS
+}
+
+/// A number that's exact while it's whole, and only becomes a float when it has to.
+///
+/// Integers are promoted to floats when they're combined with a float, when division doesn't
+/// divide evenly, and when they're raised to a negative power.
+#[derive(Clone)]
+pub enum Number {
+	Integer(BigInt),
+	Float(f64)
+}
 
 impl Number {
 	pub fn new(num: f64) -> Number {
-		Number(num)
+		Number::Float(num)
 	}
 
 	pub fn is_integer(&self) -> bool {
-		(self.0 as u64 as f64) == self.0
+		match self {
+			Number::Integer(_) => true,
+			Number::Float(float) => float.is_finite() && float.fract() == 0.0
+		}
 	}
 
+	/// Truncates floats towards zero. Floats that aren't finite are left alone
 	pub fn into_integer(self) -> Number {
-		Number((self.0 as u64) as f64)
+		match self {
+			Number::Float(float) => BigInt::from_f64(float.trunc()).map(Number::Integer).unwrap_or(Number::Float(float)),
+			integer => integer
+		}
+	}
+
+	/// The exact value of this number, if it's whole
+	pub fn to_bigint(&self) -> Option<BigInt> {
+		match self {
+			Number::Integer(int) => Some(int.clone()),
+			Number::Float(float) if self.is_integer() => BigInt::from_f64(*float),
+			Number::Float(_) => None
+		}
+	}
+
+	/// This number as an `isize`, if it's whole and fits
+	pub fn to_isize(&self) -> Option<isize> {
+		self.to_bigint().and_then(|int| int.to_isize())
+	}
+
+	pub fn to_f64(&self) -> f64 {
+		match self {
+			Number::Integer(int) => int.to_f64().unwrap_or_else(||
+				if int.is_negative() { std::f64::NEG_INFINITY } else { std::f64::INFINITY }
+			),
+			Number::Float(float) => *float
+		}
+	}
+
+	pub fn abs(&self) -> Number {
+		match self {
+			Number::Integer(int) => Number::Integer(int.abs()),
+			Number::Float(float) => Number::Float(float.abs())
+		}
+	}
+
+	/// Raises this to `exp`, or returns `None` if the result would have more than `MAX_BITS` bits
+	pub fn pow(&self, exp: &Number) -> Option<Number> {
+		if let (Number::Integer(base), Number::Integer(exp)) = (self, exp) {
+			if let Some(exp) = exp.to_usize() {
+				if pow_is_too_large(base, exp) {
+					return None;
+				}
+				return Some(Number::Integer(num_traits::pow(base.clone(), exp)));
+			}
+		}
+
+		Some(Number::Float(self.to_f64().powf(exp.to_f64())))
+	}
+
+	/// Rounds to `places` digits after the decimal point; whole results are exact
+	pub fn round(&self, places: i32) -> Number {
+		match self {
+			Number::Integer(_) if places >= 0 => self.clone(),
+			_ if places == 0 => Number::Float(self.to_f64().round()).into_integer(),
+			_ => {
+				let scale = 10f64.powi(places);
+				Number::Float((self.to_f64() * scale).round() / scale)
+			}
+		}
+	}
+}
+
+impl Default for Number {
+	fn default() -> Number {
+		Number::Integer(BigInt::zero())
+	}
+}
+
+impl<'a> Add<&'a Number> for &'a Number {
+	type Output = Number;
+	fn add(self, rhs: &'a Number) -> Number {
+		match (self, rhs) {
+			(Number::Integer(lhs), Number::Integer(rhs)) => Number::Integer(lhs + rhs),
+			(lhs, rhs) => Number::Float(lhs.to_f64() + rhs.to_f64())
+		}
+	}
+}
+
+impl<'a> Sub<&'a Number> for &'a Number {
+	type Output = Number;
+	fn sub(self, rhs: &'a Number) -> Number {
+		match (self, rhs) {
+			(Number::Integer(lhs), Number::Integer(rhs)) => Number::Integer(lhs - rhs),
+			(lhs, rhs) => Number::Float(lhs.to_f64() - rhs.to_f64())
+		}
+	}
+}
+
+impl<'a> Mul<&'a Number> for &'a Number {
+	type Output = Number;
+	fn mul(self, rhs: &'a Number) -> Number {
+		match (self, rhs) {
+			(Number::Integer(lhs), Number::Integer(rhs)) => Number::Integer(lhs * rhs),
+			(lhs, rhs) => Number::Float(lhs.to_f64() * rhs.to_f64())
+		}
+	}
+}
+
+impl<'a> Div<&'a Number> for &'a Number {
+	type Output = Number;
+	fn div(self, rhs: &'a Number) -> Number {
+		match (self, rhs) {
+			(Number::Integer(lhs), Number::Integer(rhs)) if !rhs.is_zero() && lhs.is_multiple_of(rhs) =>
+				Number::Integer(lhs / rhs),
+			(lhs, rhs) => Number::Float(lhs.to_f64() / rhs.to_f64())
+		}
+	}
+}
+
+impl<'a> Rem<&'a Number> for &'a Number {
+	type Output = Number;
+	// like floats, the result has the sign of the lhs
+	fn rem(self, rhs: &'a Number) -> Number {
+		match (self, rhs) {
+			(Number::Integer(lhs), Number::Integer(rhs)) if !rhs.is_zero() => Number::Integer(lhs % rhs),
+			(lhs, rhs) => Number::Float(lhs.to_f64() % rhs.to_f64())
+		}
+	}
+}
+
+impl<'a> Neg for &'a Number {
+	type Output = Number;
+	fn neg(self) -> Number {
+		match self {
+			Number::Integer(int) => Number::Integer(-int),
+			Number::Float(float) => Number::Float(-float)
+		}
+	}
+}
+
+impl PartialEq for Number {
+	fn eq(&self, rhs: &Number) -> bool {
+		self.partial_cmp(rhs) == Some(Ordering::Equal)
+	}
+}
+
+impl PartialOrd for Number {
+	fn partial_cmp(&self, rhs: &Number) -> Option<Ordering> {
+		match (self, rhs) {
+			(Number::Integer(lhs), Number::Integer(rhs)) => Some(lhs.cmp(rhs)),
+			(lhs, rhs) => lhs.to_f64().partial_cmp(&rhs.to_f64())
+		}
 	}
 }
 
 impl Display for Number {
 	fn fmt(&self, f: &mut Formatter) -> fmt::Result {
-		Display::fmt(&self.0, f)
+		match self {
+			Number::Integer(int) => Display::fmt(int, f),
+			Number::Float(float) => Display::fmt(float, f)
+		}
 	}
 }
 
 impl Debug for Number {
 	fn fmt(&self, f: &mut Formatter) -> fmt::Result {
-		write!(f, "Number({:?})", self.0)
+		match self {
+			Number::Integer(int) => write!(f, "Number({})", int),
+			Number::Float(float) => write!(f, "Number({:?})", float)
+		}
 	}
 }
 
-impl AsRef<f64> for Number {
-	fn as_ref(&self) -> &f64 {
-		&self.0
+impl From<BigInt> for Number {
+	fn from(int: BigInt) -> Number {
+		Number::Integer(int)
 	}
 }
 
 macro_rules! impl_conversion {
-	($($ty:ty)*) => {
+	(integer $($ty:ty, $to:ident);*) => {
 		$(
 			impl From<$ty> for Number {
 				fn from(num: $ty) -> Number {
-					Number(num as f64)
+					Number::Integer(BigInt::from(num))
 				}
 			}
 
 			impl From<Number> for $ty {
+				// saturates when the number doesn't fit, like casting a float does
 				fn from(num: Number) -> $ty {
-					num.0 as $ty
+					match num {
+						Number::Integer(int) => int.$to().unwrap_or_else(||
+							if int.is_negative() { <$ty>::min_value() } else { <$ty>::max_value() }
+						),
+						Number::Float(float) => float as $ty
+					}
+				}
+			}
+
+			impl IntoObject for $ty {
+				fn into_object(self) -> Object {
+					super::TypedObject::new_num(self).objectify()
+				}
+			}
+		)*
+	};
+	(float $($ty:ty)*) => {
+		$(
+			impl From<$ty> for Number {
+				fn from(num: $ty) -> Number {
+					Number::Float(num as f64)
+				}
+			}
+
+			impl From<Number> for $ty {
+				fn from(num: Number) -> $ty {
+					num.to_f64() as $ty
 				}
 			}
 
@@ -61,36 +265,80 @@ macro_rules! impl_conversion {
 	}
 }
 
-impl_conversion!(
-	i8 i16 i32 i64 i128 isize
-	u8 u16 u32 u64 u128 usize
-	f32 f64
+impl_conversion!(integer
+	i8, to_i8; i16, to_i16; i32, to_i32; i64, to_i64; i128, to_i128; isize, to_isize;
+	u8, to_u8; u16, to_u16; u32, to_u32; u64, to_u64; u128, to_u128; usize, to_usize
 );
+impl_conversion!(float f32 f64);
+
+impl IntoObject for BigInt {
+	fn into_object(self) -> Object {
+		super::TypedObject::new_num(self).objectify()
+	}
+}
 
 impl_typed_object!(Number, new_num, downcast_num, is_num);
 impl_quest_conversion!("@num" (as_num_obj is_num) (into_num downcast_num) -> Number);
 
 macro_rules! binary_oper {
-	($this:ident $oper:tt $rhs:ident) => (($this.0 $oper $rhs.into_num()?.0).into_object());
-	(bitwise; $this:ident $oper:tt $rhs:ident) => {
-		if !$this.is_integer() {
-			return Err(BadArgument {
+	// whole numbers stay exact when they're combined with rationals
+	(@rational; $this:ident $oper:tt $rhs:ident) => {
+		if let (Some(lhs), Some(rhs)) = (Rational::from_number(&$this), $rhs.downcast_rational()) {
+			return Ok(lhs.into_object().call_attr(stringify!($oper), &[&rhs.into_object()])?);
+		}
+	};
+	($this:ident $oper:tt $rhs:ident) => {{
+		binary_oper!(@rational; $this $oper $rhs);
+		(&$this $oper &$rhs.into_num()?).into_object()
+	}};
+	(cmp; $this:ident $oper:tt $rhs:ident) => {{
+		binary_oper!(@rational; $this $oper $rhs);
+		($this $oper $rhs.into_num()?).into_object()
+	}};
+	(@whole; $this:ident $rhs:ident) => {
+		match ($this.to_bigint(), $rhs.into_num()?.to_bigint()) {
+			(Some(lhs), Some(rhs)) => (lhs, rhs),
+			(None, _) => return Err(BadArgument {
 				func: function!(),
 				msg: "lhs isn't a whole number",
 				position: 0,
-				obj: unimplemented!("TODO: $this.clone()")
-			})
-		} else if !$rhs.into_num()?.is_integer()  {
-			return Err(BadArgument {
+				obj: $this.into_object()
+			}),
+			(_, None) => return Err(BadArgument {
 				func: function!(),
 				msg: "rhs isn't a whole number",
-				position: 0,
-				obj: unimplemented!("TODO: $rhs.clone()")
+				position: 1,
+				obj: $rhs.clone()
 			})
+		}
+	};
+	(bitwise; $this:ident $oper:tt $rhs:ident) => {{
+		let (lhs, rhs) = binary_oper!(@whole; $this $rhs);
+		(&lhs $oper &rhs).into_object()
+	}};
+	(shift; $this:ident $oper:tt $rhs:ident) => {{
+		let (lhs, rhs) = binary_oper!(@whole; $this $rhs);
+		let amount = rhs.abs().to_usize().ok_or_else(|| BadArgument {
+			func: function!(),
+			msg: "shift amount is too large",
+			position: 1,
+			obj: $rhs.clone()
+		})?;
+
+		// shifting by a negative amount shifts the other way
+		if rhs.is_negative() == (stringify!($oper) == "<<") {
+			(lhs >> amount).into_object()
+		} else if !lhs.is_zero() && (lhs.bits() as u64).saturating_add(amount as u64) > MAX_BITS {
+			return Err(BadArgument {
+				func: function!(),
+				msg: "result is too large",
+				position: 1,
+				obj: $rhs.clone()
+			});
 		} else {
-			((($this.0 as u64) $oper ($rhs.into_num()?.0 as u64)) as f64).into_object()
+			(lhs << amount).into_object()
 		}
-	}
+	}}
 }
 
 impl_type! { for Number, downcast_fn=downcast_num;
@@ -99,7 +347,7 @@ impl_type! { for Number, downcast_fn=downcast_num;
 	}
 
 	fn "@bool" (this) {
-		(this.0 != 0.0).into_object()
+		(this != Number::default()).into_object()
 	}
 
 	fn "@text" (this) {
@@ -107,16 +355,16 @@ impl_type! { for Number, downcast_fn=downcast_num;
 	}
 
 	fn "round" (this; places = 0.into_object()) {
-		if places.into_num()?.0 != 0_f64 {
-			unimplemented!("todo: round to non-integers");
-		}
-		this.0.round().into_object()
+		let bad_places = |msg| BadArgument { func: function!(), msg, position: 1, obj: places.clone() };
+		let places = places.into_num()?.to_isize().ok_or_else(|| bad_places("places isn't a whole number"))?;
+		let places = i32::try_from(places).map_err(|_| bad_places("places is out of range"))?;
+		this.round(places).into_object()
 	}
 
 	fn "()" (@this, rhs) { this.call_attr("*", &[rhs])? }
 
-	fn "-@" (this) { (-this.0.abs()).into_object() }
-	fn "+@" (this) { ( this.0.abs()).into_object() } // note this forced even negative numebrs to be positive
+	fn "-@" (this) { (-&this.abs()).into_object() }
+	fn "+@" (this) { this.abs().into_object() } // note this forced even negative numebrs to be positive
 
 	fn "+" (this, rhs) { binary_oper!(this + rhs) }
 	fn "-" (this, rhs) { binary_oper!(this - rhs) }
@@ -124,24 +372,108 @@ impl_type! { for Number, downcast_fn=downcast_num;
 	fn "/" (this, rhs) { binary_oper!(this / rhs) }
 	fn "%" (this, rhs) { binary_oper!(this % rhs) }
 	// fn "^" (@this, rhs) { this.call_attr("**", &[rhs])? }
-	fn "**" (this, rhs) { this.0.powf(rhs.into_num()?.0).into_object() }
+	fn "**" (this, rhs) {
+		this.pow(&rhs.into_num()?).ok_or_else(|| BadArgument {
+			func: function!(),
+			msg: "result is too large",
+			position: 1,
+			obj: rhs.clone()
+		})?.into_object()
+	}
+
+	// `a..b` includes `b`, and `a...b` doesn't
+	fn ".." (@this, rhs) { Range::from_objects(function!(), this, rhs, &1.into_object(), true)?.into_object() }
+	fn "..." (@this, rhs) { Range::from_objects(function!(), this, rhs, &1.into_object(), false)?.into_object() }
 
-	fn "==" (this, rhs) { binary_oper!(this == rhs) }
-	fn "<" (this, rhs) { binary_oper!(this < rhs) }
-	fn "<=" (this, rhs) { binary_oper!(this <= rhs) }
-	fn ">" (this, rhs) { binary_oper!(this > rhs) }
-	fn ">=" (this, rhs) { binary_oper!(this >= rhs) }
+	fn "==" (this, rhs) { binary_oper!(cmp; this == rhs) }
+	fn "<" (this, rhs) { binary_oper!(cmp; this < rhs) }
+	fn "<=" (this, rhs) { binary_oper!(cmp; this <= rhs) }
+	fn ">" (this, rhs) { binary_oper!(cmp; this > rhs) }
+	fn ">=" (this, rhs) { binary_oper!(cmp; this >= rhs) }
 
 	fn "<=>" (this, rhs) {
-		let (this, rhs) = (this.0, rhs.into_num()?.0);
-		if this < rhs { (-1).into_object() } // yes i can match with `cmp`, but that's a pain
-		else if this == rhs { 0.into_object() }
-		else { 1.into_object() }
+		match this.partial_cmp(&rhs.into_num()?) {
+			Some(Ordering::Less) => (-1).into_object(),
+			Some(Ordering::Equal) => 0.into_object(),
+			Some(Ordering::Greater) => 1.into_object(),
+			None => Object::new_null()
+		}
 	}
 
 	fn "&" (this, rhs) { binary_oper!(bitwise; this & rhs) }
 	fn "|" (this, rhs) { binary_oper!(bitwise; this | rhs) }
 	fn "^" (this, rhs) { binary_oper!(bitwise; this ^ rhs) }
-	fn "<<" (this, rhs) { binary_oper!(bitwise; this << rhs) }
-	fn ">>" (this, rhs) { binary_oper!(bitwise; this >> rhs) }
-}
\ No newline at end of file
+	fn "<<" (this, rhs) { binary_oper!(shift; this << rhs) }
+	fn ">>" (this, rhs) { binary_oper!(shift; this >> rhs) }
+}
+
+#[cfg(test)]
+mod tests {
+	use super::Number;
+	use num_bigint::BigInt;
+
+	fn int(num: i64) -> Number {
+		Number::from(num)
+	}
+
+	#[test]
+	fn integers_stay_exact() {
+		let big = &int(1 << 53) + &int(1);
+		assert_eq!(big.to_string(), "9007199254740993");
+		assert!(big.is_integer());
+
+		let huge = int(10).pow(&int(30)).unwrap();
+		assert_eq!(huge.to_string(), "1000000000000000000000000000000");
+	}
+
+	#[test]
+	fn promotion_to_floats() {
+		assert_eq!((&int(4) / &int(2)).to_string(), "2");
+		assert_eq!((&int(1) / &int(2)).to_string(), "0.5");
+		assert_eq!((&int(1) + &Number::new(0.5)).to_string(), "1.5");
+		assert_eq!(int(2).pow(&int(-1)).unwrap().to_string(), "0.5");
+	}
+
+	#[test]
+	fn negative_integers_are_kept() {
+		assert_eq!(int(-7).into_integer().to_string(), "-7");
+		assert_eq!(Number::new(-7.9).into_integer().to_string(), "-7");
+		assert_eq!((&int(-7) % &int(3)).to_string(), "-1");
+		assert_eq!(int(-7).to_isize(), Some(-7));
+	}
+
+	#[test]
+	fn mixed_comparisons() {
+		assert_eq!(int(1), Number::new(1.0));
+		assert!(int(1) < Number::new(1.5));
+		assert!(Number::new(std::f64::NAN) != Number::new(std::f64::NAN));
+	}
+
+	#[test]
+	fn size_limits() {
+		use crate::object::IntoObject;
+		assert!(int(2).pow(&int(1 << 30)).is_none());
+		assert_eq!(int(-1).pow(&int(1 << 30)), Some(int(1)));
+		assert!(int(2).pow(&int(1000)).is_some());
+		assert!(!super::pow_is_too_large(&BigInt::from(2), 10_000_000));
+		assert!(super::pow_is_too_large(&BigInt::from(2), super::MAX_BITS as usize));
+		assert!(!super::pow_is_too_large(&BigInt::from(0), usize::max_value()));
+
+		let round = |num: f64, places: i64| num.into_object().call_attr("round", &[&places.into_object()]);
+		assert!(round(1.5, 1 << 32).is_err());
+		assert_eq!(round(2.5, 0).unwrap(), 3.into_object());
+
+		let shift = |lhs: i64, oper: &str, rhs: i64| lhs.into_object().call_attr(oper, &[&rhs.into_object()]);
+		assert!(shift(1, "<<", 1 << 30).is_err());
+		assert!(shift(1, ">>", -(1 << 30)).is_err());
+		assert_eq!(shift(0, "<<", 1 << 30).unwrap(), 0.into_object());
+		assert_eq!(shift(1, ">>", 1 << 30).unwrap(), 0.into_object());
+	}
+
+	#[test]
+	fn saturating_conversions() {
+		let big = Number::from("100000000000000000000".parse::<BigInt>().unwrap());
+		assert_eq!(i32::from(big), std::i32::MAX);
+		assert_eq!(u8::from(int(-1)), 0);
+	}
+}
@@ -19,6 +19,7 @@ pub enum Oper {
 
 	Assign, ArrowRight, ArrowLeft,
 	Period, ColonColon, Comma, Endline,
+	Range, RangeExcl,
 
 	Execute, Call,
 	Other(Arity, Precedence, RustFn) // bool is whether or not is_l_to_r_assoc; `+` is true
@@ -50,6 +51,7 @@ pub enum Precedence {
 	BitShl_BitShr,
 	BitAnd,
 	BitOr_BitXor,
+	Range,
 	Lth_Gth_Leq_Geq,
 	Eq_Ne_Cmp,
 	And,
@@ -84,11 +86,11 @@ impl Oper {
 					break;
 				} else {
 					trace!(target: "execute", "Oper={:?} found an oper more tightly bound={:?}", self, oper);
-					object = oper.evaluate(parser)?;
+					object = oper.evaluate(parser).map_err(|err| object.locate_err(err))?;
 				}
 			}
 
-			object = object.evaluate(parser)?;
+			object = object.evaluate(parser).map_err(|err| object.locate_err(err))?;
 			Environment::current().read().stack.write().push(object);
 		}
 
@@ -104,7 +106,8 @@ impl Oper {
 					.stack.write()
 					.pop().ok_or_else(|| Error::MissingArgument {
 						func: self.sigil(),
-						pos: $err_pos
+						pos: $err_pos,
+						param: None
 					})?
 			}
 		}
@@ -114,7 +117,8 @@ impl Oper {
 				self.get_net_obj(parser)?
 					.ok_or_else(|| Error::MissingArgument {
 						func: self.sigil(),
-						pos: $err_pos
+						pos: $err_pos,
+						param: None
 					})?
 			}
 		}
@@ -156,7 +160,7 @@ impl Oper {
 		  BitShlEq, BitShrEq, BitAndEq, BitOrEq, BitXorEq,
 		  Eql, Neq, Lth, Leq, Gth, Geq, Cmp, And, Or, Not,
 		  Assign, ArrowRight, ArrowLeft, Period, ColonColon,
-		  Comma, Endline, Execute, Call]
+		  Comma, Endline, Range, RangeExcl, Execute, Call]
 	}
 
 	// i think it might be interesting to have this take from the current environment
@@ -197,6 +201,8 @@ impl Oper {
 			BitAnd            => Precedence::BitAnd,
 			BitOr
 			  | BitXor        => Precedence::BitOr_BitXor,
+			Range
+			  | RangeExcl     => Precedence::Range,
 			Lth
 			  | Gth
 			  | Leq
@@ -237,6 +243,7 @@ impl Oper {
 			And => "and", Or => "or", Not => "not",
 			Assign => "=", ArrowRight => "->", ArrowLeft => "<-",
 			Period => ".", ColonColon => "::", Endline => ";", Comma => ",",
+			Range => "..", RangeExcl => "...",
 			Execute => "!", Call => ":",
 			Other(_, _, _) => unreachable!("Shouldn't be calling `sigil` on a rustfn")
 		}
@@ -251,7 +258,7 @@ impl Oper {
 
 	fn call(&self, args: &[&Object]) -> Result<Object> {
 		macro_rules! arg {
-			($pos:expr) => (args.get($pos).ok_or_else(|| $crate::Error::MissingArgument{ func: self.sigil(), pos: $pos })?);
+			($pos:expr) => (args.get($pos).ok_or_else(|| $crate::Error::MissingArgument{ func: self.sigil(), pos: $pos, param: None })?);
 		}
 
 		if *self == Endline {
@@ -0,0 +1,298 @@
+use crate::{Object, Shared, Result, Error, IntoObject, Environment};
+use crate::parse::{Parser, Location};
+use crate::collections::Mapping;
+use crate::object::typed::Variable;
+use std::fmt::{self, Display, Formatter};
+
+/// One parameter of a block. `default` is the code for its default value and where it starts.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Param {
+	pub name: &'static str,
+	pub default: Option<(String, Location)>
+}
+
+/// The parameters that a block declares at the start of its body, like `{ |a, b = 2, *rest, key: 3| ... }`.
+///
+/// Positional parameters come first, then the optional `*rest`, which collects any extra
+/// positional arguments, and then keyword parameters (`key:` or `key: default`). If a block has
+/// keyword parameters and its last argument is a `Map`, that map supplies them.
+///
+/// Defaults are run each time they're needed, after the parameters before them have been bound,
+/// so `|a, b = a * 2|` works. A `|` inside a default has to be in parens.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct Params {
+	positional: Vec<Param>,
+	rest: Option<&'static str>,
+	keywords: Vec<Param>
+}
+
+// the end of the parameter list starting at `text[0]` (a `|`), skipping over anything nested
+fn closing_bar(text: &str) -> Option<usize> {
+	let mut depth = 0usize;
+	let mut chars = text.char_indices().skip(1);
+
+	while let Some((idx, chr)) = chars.next() {
+		match chr {
+			'(' | '[' | '{' => depth += 1,
+			')' | ']' | '}' => depth = depth.checked_sub(1)?,
+			'\'' | '"' | '`' => {
+				while let Some((_, next)) = chars.next() {
+					if next == '\\' {
+						chars.next();
+					} else if next == chr {
+						break
+					}
+				}
+			},
+			'|' if depth == 0 => return Some(idx),
+			_ => {}
+		}
+	}
+
+	None
+}
+
+// splits `text` on the commas that aren't nested, returning each piece with its byte offset
+fn split_params(text: &str) -> Vec<(usize, &str)> {
+	let mut pieces = Vec::new();
+	let mut depth = 0isize;
+	let mut start = 0;
+	let mut quote = None;
+	let mut escaped = false;
+
+	for (idx, chr) in text.char_indices() {
+		match (quote, chr) {
+			(Some(_), _) if escaped => escaped = false,
+			(Some(_), '\\') => escaped = true,
+			(Some(q), _) if q == chr => quote = None,
+			(Some(_), _) => {},
+			(None, '\'') | (None, '"') | (None, '`') => quote = Some(chr),
+			(None, '(') | (None, '[') | (None, '{') => depth += 1,
+			(None, ')') | (None, ']') | (None, '}') => depth -= 1,
+			(None, ',') if depth == 0 => {
+				pieces.push((start, &text[start..idx]));
+				start = idx + 1;
+			},
+			_ => {}
+		}
+	}
+
+	pieces.push((start, &text[start..]));
+	pieces
+}
+
+fn is_name(text: &str) -> bool {
+	let mut chars = text.chars();
+	chars.next().map(|chr| chr.is_alphabetic() || chr == '_').unwrap_or(false)
+		&& chars.all(|chr| chr.is_alphanumeric() || chr == '_')
+}
+
+impl Params {
+	/// Parses the parameter list at the start of `body`, which starts at `location`.
+	///
+	/// Returns the parameters and how many bytes of `body` they took up, or `None` if `body`
+	/// doesn't start with a well-formed parameter list.
+	pub fn parse(body: &str, location: &Location) -> Option<(Params, usize)> {
+		let start = body.len() - body.trim_start().len();
+		if !body[start..].starts_with('|') {
+			return None;
+		}
+
+		let end = start + closing_bar(&body[start..])?;
+		let inner_start = start + 1;
+		let mut params = Params::default();
+
+		if body[inner_start..end].trim().is_empty() {
+			return Some((params, end + 1));
+		}
+
+		for (offset, piece) in split_params(&body[inner_start..end]) {
+			let piece_start = inner_start + offset + (piece.len() - piece.trim_start().len());
+			let piece = piece.trim();
+			let intern = |name: &str| Variable::from_string(name.to_string()).into_inner();
+
+			// the default starting at `at` in `piece`, and where it is in the source
+			let default = |at: usize| {
+				let code = piece[at..].trim_start();
+				let code_start = piece_start + at + (piece[at..].len() - code.len());
+				if code.is_empty() {
+					None
+				} else {
+					Some((code.to_string(), location.after(&body[..code_start])))
+				}
+			};
+
+			if piece.starts_with('*') {
+				if params.rest.is_some() || !params.keywords.is_empty() || !is_name(&piece[1..]) {
+					return None;
+				}
+				params.rest = Some(intern(&piece[1..]));
+			} else if let Some(colon) = piece.find(':').filter(|&colon| is_name(piece[..colon].trim_end())) {
+				params.keywords.push(Param { name: intern(piece[..colon].trim_end()), default: default(colon + 1) });
+			} else if let Some(eq) = piece.find('=').filter(|&eq| is_name(piece[..eq].trim_end())) {
+				if params.rest.is_some() || !params.keywords.is_empty() {
+					return None;
+				}
+				let default = default(eq + 1);
+				if default.is_none() {
+					return None;
+				}
+				params.positional.push(Param { name: intern(piece[..eq].trim_end()), default });
+			} else if is_name(piece) {
+				// required positional parameters can't come after optional ones
+				if params.rest.is_some() || !params.keywords.is_empty() || params.positional.iter().any(|param| param.default.is_some()) {
+					return None;
+				}
+				params.positional.push(Param { name: intern(piece), default: None });
+			} else {
+				return None;
+			}
+		}
+
+		Some((params, end + 1))
+	}
+
+	pub fn is_empty(&self) -> bool {
+		self.positional.is_empty() && self.rest.is_none() && self.keywords.is_empty()
+	}
+
+	// runs a default value's code in `env`
+	fn default_value(code: &str, location: &Location, env: &Shared<Environment>) -> Result<Object> {
+		let parser = Shared::new(Parser::from_str_at(code.to_string(), location.clone()));
+		let default_env = Environment::execute(Environment::new(parser, Some(env.clone()), None, None))?;
+		let value = default_env.read().stack.write().pop();
+		Ok(value.unwrap_or_else(Object::new_null))
+	}
+
+	/// Sets the parameters as locals in `env`, taking them from `args`. `func` is used for errors.
+	pub fn bind(&self, func: &'static str, args: &[Object], env: &Shared<Environment>) -> Result<()> {
+		let mut args = args.to_vec();
+		let kwargs = if !self.keywords.is_empty() && args.last().map(Object::is_map).unwrap_or(false) {
+			args.pop().and_then(|map| map.downcast_map())
+		} else {
+			None
+		};
+
+		let set = |name: &'static str, value: Object| { env.write().set(Variable::new(name).into_object(), value); };
+		let missing = |pos: usize, param: &Param| Error::MissingArgument { func, pos, param: Some(param.name) };
+
+		for (pos, param) in self.positional.iter().enumerate() {
+			let value = match (args.get(pos), &param.default) {
+				(Some(arg), _) => arg.clone(),
+				(None, Some((code, location))) => Params::default_value(code, location, env)?,
+				(None, None) => return Err(missing(pos, param))
+			};
+			set(param.name, value);
+		}
+
+		if let Some(rest) = self.rest {
+			let extra = args.get(self.positional.len()..).unwrap_or(&[]).to_vec();
+			set(rest, extra.into_object());
+		}
+
+		// keyword arguments can be given as either texts or variables
+		let mut kwargs = kwargs.map(|kwargs| kwargs.pairs()).unwrap_or_default().into_iter()
+			.map(|(key, value)| (key.downcast_text().map(|text| text.into_inner())
+				.or_else(|| key.downcast_var().map(|var| var.into_inner().to_string())), key, value))
+			.collect::<Vec<_>>();
+
+		if let Some((_, key, _)) = kwargs.iter().find(|(name, ..)|
+			!self.keywords.iter().any(|param| name.as_ref().map(String::as_str) == Some(param.name)))
+		{
+			return Err(Error::BadArgument {
+				func,
+				msg: "unknown keyword argument",
+				position: args.len(),
+				obj: key.clone()
+			});
+		}
+
+		for (idx, param) in self.keywords.iter().enumerate() {
+			let given = kwargs.iter()
+				.position(|(name, ..)| name.as_ref().map(String::as_str) == Some(param.name))
+				.map(|pos| kwargs.remove(pos).2);
+
+			let value = match (given, &param.default) {
+				(Some(value), _) => value,
+				(None, Some((code, location))) => Params::default_value(code, location, env)?,
+				(None, None) => return Err(missing(self.positional.len() + idx, param))
+			};
+			set(param.name, value);
+		}
+
+		Ok(())
+	}
+
+	/// Describes each parameter as a map of its `name`, `kind` (`positional`, `rest` or
+	/// `keyword`) and the code for its `default`, if it has one.
+	pub fn describe(&self) -> Object {
+		let describe = |param: &Param, kind: &str| super::Map::from_pairs(vec![
+			("name".to_string().into_object(), param.name.to_string().into_object()),
+			("kind".to_string().into_object(), kind.to_string().into_object()),
+			("default".to_string().into_object(), param.default.as_ref()
+				.map(|(code, _)| code.clone().into_object())
+				.unwrap_or_else(Object::new_null))
+		]).into_object();
+
+		let mut described = self.positional.iter().map(|param| describe(param, "positional")).collect::<Vec<_>>();
+		described.extend(self.rest.map(|name| describe(&Param { name, default: None }, "rest")));
+		described.extend(self.keywords.iter().map(|param| describe(param, "keyword")));
+		described.into_object()
+	}
+}
+
+impl Display for Param {
+	fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+		write!(f, "{}", self.name)?;
+		if let Some((code, _)) = &self.default {
+			write!(f, " = {}", code)?;
+		}
+		Ok(())
+	}
+}
+
+impl Display for Params {
+	fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+		let mut params = self.positional.iter().map(ToString::to_string).collect::<Vec<_>>();
+		params.extend(self.rest.map(|rest| format!("*{}", rest)));
+		params.extend(self.keywords.iter().map(|param| match &param.default {
+			Some((code, _)) => format!("{}: {}", param.name, code),
+			None => format!("{}:", param.name)
+		}));
+
+		write!(f, "|{}|", params.join(", "))
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn parse(body: &str) -> Option<(String, usize)> {
+		Params::parse(body, &Location::default()).map(|(params, len)| (params.to_string(), len))
+	}
+
+	#[test]
+	fn parses() {
+		assert_eq!(parse("|a, b| a + b"), Some(("|a, b|".to_string(), 6)));
+		assert_eq!(parse(" |a, b = 2, *rest, key: 3| rest"), Some(("|a, b = 2, *rest, key: 3|".to_string(), 26)));
+		assert_eq!(parse("|a = f:[1, 2], k: (x | y)|"), Some(("|a = f:[1, 2], k: (x | y)|".to_string(), 26)));
+		assert_eq!(parse("||"), Some(("||".to_string(), 2)));
+	}
+
+	#[test]
+	fn not_params() {
+		assert_eq!(parse("a | b"), None);
+		assert_eq!(parse("|a"), None);
+		assert_eq!(parse("|a = 1, b|"), None);
+		assert_eq!(parse("|*rest, a|"), None);
+		assert_eq!(parse("|a b|"), None);
+	}
+
+	#[test]
+	fn default_locations() {
+		let (params, _) = Params::parse("\n|a = 1|", &Location::default()).unwrap();
+		let (_, location) = params.positional[0].default.clone().unwrap();
+		assert_eq!((location.line, location.col), (1, 5));
+	}
+}
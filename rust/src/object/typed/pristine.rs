@@ -27,16 +27,13 @@ basic_map! {
 		crate::object::typed::BoundObject::new(this.clone(), key.clone()).into_object()
 	}
 
+	// these go through `Object`'s `Mapping` methods, so that environments use their locals
 	fn ".=" (@this, key, val) {
-		this.map().write()
-		    .set(key.clone(), val.clone())
-		    .unwrap_or_else(Object::new_null)
+		this.clone().set(key.clone(), val.clone()).unwrap_or_else(Object::new_null)
 	}
 
 	fn ".~" (@this, key) {
-		this.map().write()
-		    .del(key)
-		    .unwrap_or_else(Object::new_null)
+		this.clone().del(key).unwrap_or_else(Object::new_null)
 	}
 
 	fn ".?" (@this, key) {
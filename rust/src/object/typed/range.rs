@@ -0,0 +1,330 @@
+use crate::{Object, Error, Result};
+use std::fmt::{self, Debug, Display, Formatter};
+use lazy_static::lazy_static;
+
+/// A lazy sequence of whole numbers, made with `start..stop` (which includes `stop`),
+/// `start...stop` (which doesn't) or `range(start, stop, step)`.
+///
+/// Like in Ruby, a range that goes the opposite way to its step is empty, so `5..1` has no
+/// elements but `(1..5).reverse()` is `5, 4, 3, 2, 1`.
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Range {
+	start: isize,
+	stop: isize,
+	step: isize,
+	inclusive: bool
+}
+
+// converts `obj` into a bound for a range
+fn to_bound(obj: &Object, func: &'static str, position: usize) -> Result<isize> {
+	obj.into_num()?.to_isize().ok_or_else(|| Error::BadArgument {
+		func,
+		msg: "ranges must be made of whole numbers",
+		position,
+		obj: obj.clone()
+	})
+}
+
+impl Range {
+	/// Returns `None` if `step` is zero
+	pub fn new(start: isize, stop: isize, step: isize, inclusive: bool) -> Option<Range> {
+		if step == 0 {
+			None
+		} else {
+			Some(Range { start, stop, step, inclusive })
+		}
+	}
+
+	/// Creates a range out of Quest objects, which have to be whole numbers. `func` is used for errors.
+	pub(crate) fn from_objects(func: &'static str, start: &Object, stop: &Object, step: &Object, inclusive: bool) -> Result<Range> {
+		let start_num = to_bound(start, func, 0)?;
+		let stop_num = to_bound(stop, func, 1)?;
+		let step_num = to_bound(step, func, 2)?;
+
+		Range::new(start_num, stop_num, step_num, inclusive).ok_or_else(|| Error::BadArgument {
+			func,
+			msg: "step is zero",
+			position: 2,
+			obj: step.clone()
+		})
+	}
+
+	pub fn start(&self) -> isize {
+		self.start
+	}
+
+	pub fn stop(&self) -> isize {
+		self.stop
+	}
+
+	pub fn step(&self) -> isize {
+		self.step
+	}
+
+	pub fn is_inclusive(&self) -> bool {
+		self.inclusive
+	}
+
+	// the first number past the end of the range. this (and the rest of the arithmetic) is done
+	// in `i128`s, as ranges can span the whole of `isize`.
+	fn end(&self) -> i128 {
+		if self.inclusive {
+			self.stop as i128 + self.step.signum() as i128
+		} else {
+			self.stop as i128
+		}
+	}
+
+	// the `index`th number, which may be past the end of the range
+	fn nth(&self, index: usize) -> isize {
+		(self.start as i128 + index as i128 * self.step as i128) as isize
+	}
+
+	/// The amount of numbers in the range, which saturates at `usize::MAX`
+	pub fn len(&self) -> usize {
+		let (distance, step) = if self.step > 0 {
+			(self.end() - self.start as i128, self.step as i128)
+		} else {
+			(self.start as i128 - self.end(), -(self.step as i128))
+		};
+
+		if distance <= 0 {
+			0
+		} else {
+			((distance + step - 1) / step).min(usize::MAX as i128) as usize
+		}
+	}
+
+	pub fn is_empty(&self) -> bool {
+		self.len() == 0
+	}
+
+	/// The `index`th number in the range, if there are that many
+	pub fn get(&self, index: usize) -> Option<isize> {
+		if index < self.len() {
+			Some(self.nth(index))
+		} else {
+			None
+		}
+	}
+
+	pub fn contains(&self, num: isize) -> bool {
+		let (num, start) = (num as i128, self.start as i128);
+		let in_bounds = if self.step > 0 {
+			start <= num && num < self.end()
+		} else {
+			self.end() < num && num <= start
+		};
+
+		in_bounds && (num - start) % self.step as i128 == 0
+	}
+
+	pub fn iter(&self) -> impl Iterator<Item=isize> {
+		let range = *self;
+		(0..self.len()).map(move |index| range.nth(index))
+	}
+
+	/// The part of this range that's at least `low` and less than `high`, without going through
+	/// the numbers outside of it
+	pub fn within(&self, low: isize, high: isize) -> Range {
+		let (start, step, len) = (self.start as i128, self.step as i128, self.len() as i128);
+		let (low, high) = (low as i128, high as i128 - 1);
+		let ceil_div = |num: i128, by: i128| -(-num).div_euclid(by);
+
+		// the indices of the first and last numbers that are in bounds
+		let (first, last) = if step > 0 {
+			(ceil_div(low - start, step), (high - start).div_euclid(step))
+		} else {
+			(ceil_div(start - high, -step), (start - low).div_euclid(-step))
+		};
+		let (first, last) = (first.max(0), last.min(len - 1));
+
+		if len == 0 || last < first {
+			Range { start: 0, stop: 0, step: self.step, inclusive: false }
+		} else {
+			Range { start: self.nth(first as usize), stop: self.nth(last as usize), step: self.step, inclusive: true }
+		}
+	}
+
+	/// The same numbers, backwards, or `None` if the step is `isize::MIN`, which can't be negated
+	pub fn reverse(&self) -> Option<Range> {
+		match self.get(self.len().wrapping_sub(1)) {
+			Some(last) => Some(Range { start: last, stop: self.start, step: self.step.checked_neg()?, inclusive: true }),
+			None => Some(*self)
+		}
+	}
+}
+
+impl Display for Range {
+	fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+		write!(f, "{}{}{}", self.start, if self.inclusive { ".." } else { "..." }, self.stop)?;
+
+		if self.step != 1 {
+			write!(f, " step {}", self.step)?;
+		}
+
+		Ok(())
+	}
+}
+
+impl Debug for Range {
+	fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+		write!(f, "Range({})", self)
+	}
+}
+
+impl_typed_object!(Range, new_range, downcast_range, is_range);
+
+impl_type! { for Range, downcast_fn=downcast_range;
+	fn "@text" (this) {
+		this.to_string().into_object()
+	}
+
+	fn "@list" (this) {
+		this.iter().map(IntoObject::into_object).collect::<Vec<_>>().into_object()
+	}
+
+	fn "@iter" (this) {
+		Iter::new(this.iter().map(IntoObject::into_object)).into_object()
+	}
+
+	fn "@bool" (this) {
+		(!this.is_empty()).into_object()
+	}
+
+	fn "==" (this, rhs) {
+		(Some(this) == rhs.downcast_range()).into_object()
+	}
+
+	fn "len" (this) {
+		this.len().into_object()
+	}
+
+	fn "start" (this) { this.start().into_object() }
+	fn "stop" (this) { this.stop().into_object() }
+
+	// things that aren't whole numbers are never in a range
+	fn "has" (this, num) {
+		let num = num.into_num()?.to_isize();
+		num.map(|num| this.contains(num)).unwrap_or(false).into_object()
+	}
+
+	fn "[]?" (@this, num) {
+		this.call_attr("has", &[num])?
+	}
+
+	// the `index`th number, counting back from the end if it's negative
+	fn "[]" (this, index) {
+		normalize_index(to_index(index, function!(), 1)?, this.len())
+			.and_then(|index| this.get(index))
+			.map(IntoObject::into_object)
+			.unwrap_or_else(Object::new_null)
+	}
+
+	fn "reverse" (this) {
+		this.reverse().ok_or_else(|| BadArgument {
+			func: function!(),
+			msg: "step is too large to reverse",
+			position: 0,
+			obj: this.into_object()
+		})?.into_object()
+	}
+
+	// the same range, but only every `step`th number; a negative step goes backwards
+	fn "step" (this, step) {
+		let by = to_index(step, function!(), 1)?;
+		let too_large = || BadArgument { func: function!(), msg: "step is too large", position: 1, obj: step.clone() };
+		let stepped = if by < 0 { this.reverse().ok_or_else(too_large)? } else { this };
+		let new_step = by.checked_abs()
+			.and_then(|by| stepped.step().checked_mul(by))
+			.ok_or_else(too_large)?;
+
+		Range::new(stepped.start(), stepped.stop(), new_step, stepped.is_inclusive())
+			.ok_or_else(|| BadArgument {
+				func: function!(),
+				msg: "step is zero",
+				position: 1,
+				obj: step.clone()
+			})?
+			.into_object()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::Range;
+
+	fn range(start: isize, stop: isize, step: isize, inclusive: bool) -> Vec<isize> {
+		Range::new(start, stop, step, inclusive).unwrap().iter().collect()
+	}
+
+	#[test]
+	fn elements() {
+		assert_eq!(range(1, 5, 1, true), vec![1, 2, 3, 4, 5]);
+		assert_eq!(range(1, 5, 1, false), vec![1, 2, 3, 4]);
+		assert_eq!(range(0, 10, 3, false), vec![0, 3, 6, 9]);
+		assert_eq!(range(1, 10, 3, true), vec![1, 4, 7, 10]);
+		assert_eq!(range(5, 1, -2, true), vec![5, 3, 1]);
+		assert_eq!(range(5, 1, 1, true), Vec::<isize>::new());
+		assert!(Range::new(0, 1, 0, false).is_none());
+	}
+
+	#[test]
+	fn contains() {
+		let range = Range::new(0, 10, 3, false).unwrap();
+		assert!(range.contains(0) && range.contains(9));
+		assert!(!range.contains(10) && !range.contains(4) && !range.contains(-3));
+
+		let backwards = Range::new(5, 1, -2, true).unwrap();
+		assert!(backwards.contains(1) && backwards.contains(3));
+		assert!(!backwards.contains(2) && !backwards.contains(7));
+	}
+
+	#[test]
+	fn limits() {
+		let whole = Range::new(isize::MIN, isize::MAX, 1, true).unwrap();
+		assert_eq!(whole.len(), usize::MAX);
+		assert!(whole.contains(isize::MIN) && whole.contains(isize::MAX));
+		assert_eq!(whole.get(1), Some(isize::MIN + 1));
+
+		let up = Range::new(0, isize::MAX, 1, true).unwrap();
+		assert_eq!(up.len(), isize::MAX as usize + 1);
+		assert!(up.contains(isize::MAX) && !up.contains(-1));
+		assert_eq!(up.reverse().unwrap().get(0), Some(isize::MAX));
+
+		let down = Range::new(isize::MAX, isize::MIN, -2, false).unwrap();
+		assert_eq!(down.len(), usize::MAX / 2 + 1);
+		assert!(down.contains(isize::MAX) && down.contains(isize::MIN + 1) && !down.contains(isize::MIN));
+	}
+
+	#[test]
+	fn within() {
+		let range = |range: Range| range.iter().collect::<Vec<_>>();
+		let huge = Range::new(0, 1_000_000_000_000_000, 2, false).unwrap();
+		assert_eq!(range(huge.within(-3, 3)), vec![0, 2]);
+		assert_eq!(range(Range::new(-5, 5, 3, true).unwrap().within(-3, 3)), vec![-2, 1]);
+		assert_eq!(range(Range::new(isize::MAX, isize::MIN, -3, true).unwrap().within(-3, 3)).len(), 2);
+		assert_eq!(range(Range::new(10, 20, 1, false).unwrap().within(-3, 3)), Vec::<isize>::new());
+	}
+
+	#[test]
+	fn reverse() {
+		let range = Range::new(0, 10, 3, false).unwrap();
+		let reversed = range.reverse().unwrap();
+		assert_eq!(reversed.iter().collect::<Vec<_>>(), vec![9, 6, 3, 0]);
+		assert_eq!(reversed.reverse().unwrap().iter().collect::<Vec<_>>(), range.iter().collect::<Vec<_>>());
+		assert_eq!(reversed.to_string(), "9..0 step -3");
+		assert!(Range::new(1, -10, isize::MIN, true).unwrap().reverse().is_none());
+	}
+
+	#[test]
+	fn large_steps() {
+		use crate::IntoObject;
+		let step = |range: Range, by: isize| range.into_object().call_attr("step", &[&by.into_object()]);
+
+		assert!(step(Range::new(0, 10, isize::MAX / 2, false).unwrap(), 3).is_err());
+		assert!(step(Range::new(0, 10, 1, false).unwrap(), isize::MIN).is_err());
+		assert!(step(Range::new(0, -10, isize::MIN, false).unwrap(), -1).is_err());
+		assert_eq!(step(Range::new(0, 10, 2, false).unwrap(), -2).unwrap(), Range::new(8, 0, -4, true).unwrap().into_object());
+	}
+}
@@ -0,0 +1,232 @@
+use crate::object::{Object, IntoObject};
+use crate::object::typed::Number;
+use crate::object::typed::number::pow_is_too_large;
+use std::fmt::{self, Debug, Display, Formatter};
+use std::cmp::Ordering;
+use num_bigint::BigInt;
+use num_rational::BigRational;
+use num_traits::{Zero, Signed, ToPrimitive};
+use lazy_static::lazy_static;
+
+/// An exact fraction, always kept in lowest terms with a positive denominator.
+///
+/// Combining a rational with a whole `Number` stays exact; combining it with a fractional
+/// `Number` gives a float.
+#[derive(Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Rational(BigRational);
+
+impl Rational {
+	/// Returns `None` if `denom` is zero
+	pub fn new(numer: BigInt, denom: BigInt) -> Option<Rational> {
+		if denom.is_zero() {
+			None
+		} else {
+			Some(Rational(BigRational::new(numer, denom)))
+		}
+	}
+
+	/// The exact rational for `num`, if it's whole
+	pub fn from_number(num: &Number) -> Option<Rational> {
+		num.to_bigint().map(|int| Rational(BigRational::from_integer(int)))
+	}
+
+	pub fn numer(&self) -> &BigInt {
+		self.0.numer()
+	}
+
+	pub fn denom(&self) -> &BigInt {
+		self.0.denom()
+	}
+
+	pub fn is_zero(&self) -> bool {
+		self.0.is_zero()
+	}
+
+	pub fn to_f64(&self) -> f64 {
+		match (self.numer().to_f64(), self.denom().to_f64()) {
+			(Some(numer), Some(denom)) => numer / denom,
+			_ => {
+				// too big for a float on its own; the whole part is close enough
+				let quotient = self.numer() / self.denom();
+				quotient.to_f64().unwrap_or(if self.0.is_negative() { std::f64::NEG_INFINITY } else { std::f64::INFINITY })
+			}
+		}
+	}
+
+	/// Whole rationals become exact `Number`s, and everything else becomes a float.
+	pub fn to_number(&self) -> Number {
+		if self.0.is_integer() {
+			Number::from(self.numer().clone())
+		} else {
+			Number::new(self.to_f64())
+		}
+	}
+
+	/// Raises this to `exp`. The error is why it couldn't be: either zero was raised to a negative
+	/// power, or the result would have more than `MAX_BITS` bits.
+	pub fn pow(&self, exp: i32) -> Result<Rational, &'static str> {
+		if exp < 0 && self.is_zero() {
+			return Err("division by zero");
+		}
+
+		let magnitude = (exp as i64).abs() as usize;
+		if pow_is_too_large(self.numer(), magnitude) || pow_is_too_large(self.denom(), magnitude) {
+			return Err("result is too large");
+		}
+
+		let numer = num_traits::pow(self.numer().clone(), magnitude);
+		let denom = num_traits::pow(self.denom().clone(), magnitude);
+		Ok(Rational(if exp < 0 { BigRational::new(denom, numer) } else { BigRational::new(numer, denom) }))
+	}
+}
+
+impl Display for Rational {
+	fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+		write!(f, "{}/{}", self.numer(), self.denom())
+	}
+}
+
+impl Debug for Rational {
+	fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+		write!(f, "Rational({}/{})", self.numer(), self.denom())
+	}
+}
+
+impl_typed_object!(Rational, new_rational, downcast_rational, is_rational);
+
+/// The right-hand side of an operation: rationals and whole numbers are exact, everything else
+/// is converted via `@num` and ends up as a float.
+enum Operand {
+	Exact(Rational),
+	Inexact(f64)
+}
+
+impl Operand {
+	fn from_object(obj: &Object) -> crate::Result<Operand> {
+		if let Some(rational) = obj.downcast_rational() {
+			return Ok(Operand::Exact(rational));
+		}
+
+		let num = obj.into_num()?;
+		Ok(Rational::from_number(&num).map(Operand::Exact).unwrap_or_else(|| Operand::Inexact(num.to_f64())))
+	}
+}
+
+macro_rules! binary_oper {
+	($this:ident $oper:tt $rhs:ident) => {
+		match Operand::from_object($rhs)? {
+			Operand::Exact(rhs) => Rational(&$this.0 $oper &rhs.0).into_object(),
+			Operand::Inexact(rhs) => ($this.to_f64() $oper rhs).into_object()
+		}
+	};
+	(nonzero; $this:ident $oper:tt $rhs:ident) => {
+		match Operand::from_object($rhs)? {
+			Operand::Exact(ref rhs) if rhs.is_zero() => return Err(BadArgument {
+				func: function!(),
+				msg: "division by zero",
+				position: 1,
+				obj: $rhs.clone()
+			}),
+			Operand::Exact(rhs) => Rational(&$this.0 $oper &rhs.0).into_object(),
+			Operand::Inexact(rhs) => ($this.to_f64() $oper rhs).into_object()
+		}
+	};
+	(cmp; $this:ident $oper:tt $rhs:ident) => {
+		match Operand::from_object($rhs)? {
+			Operand::Exact(rhs) => ($this $oper rhs).into_object(),
+			Operand::Inexact(rhs) => ($this.to_f64() $oper rhs).into_object()
+		}
+	}
+}
+
+impl_type! { for Rational, downcast_fn=downcast_rational;
+	fn "@num" (this) { this.to_number().into_object() }
+	fn "@text" (this) { this.to_string().into_object() }
+	fn "@bool" (this) { (!this.is_zero()).into_object() }
+
+	fn "numer" (this) { this.numer().clone().into_object() }
+	fn "denom" (this) { this.denom().clone().into_object() }
+
+	fn "-@" (this) { Rational(-this.0).into_object() }
+	fn "+@" (this) { Rational(this.0.abs()).into_object() }
+
+	fn "+" (this, rhs) { binary_oper!(this + rhs) }
+	fn "-" (this, rhs) { binary_oper!(this - rhs) }
+	fn "*" (this, rhs) { binary_oper!(this * rhs) }
+	fn "/" (this, rhs) { binary_oper!(nonzero; this / rhs) }
+	fn "%" (this, rhs) { binary_oper!(nonzero; this % rhs) }
+
+	fn "**" (this, rhs) {
+		let exp = rhs.into_num()?;
+		match exp.to_isize().filter(|&exp| exp.abs() <= i32::max_value() as isize) {
+			Some(exp) => this.pow(exp as i32)
+				.map_err(|msg| BadArgument { func: function!(), msg, position: 1, obj: rhs.clone() })?
+				.into_object(),
+			None => this.to_f64().powf(exp.to_f64()).into_object()
+		}
+	}
+
+	fn "==" (this, rhs) {
+		// unlike the other comparisons, `==` doesn't fail for things that aren't numbers
+		match rhs.downcast_rational().map(Operand::Exact).or_else(|| rhs.downcast_num().map(|num|
+			Rational::from_number(&num).map(Operand::Exact).unwrap_or_else(|| Operand::Inexact(num.to_f64()))
+		)) {
+			Some(Operand::Exact(rhs)) => (this == rhs).into_object(),
+			Some(Operand::Inexact(rhs)) => (this.to_f64() == rhs).into_object(),
+			None => false.into_object()
+		}
+	}
+
+	fn "<" (this, rhs) { binary_oper!(cmp; this < rhs) }
+	fn "<=" (this, rhs) { binary_oper!(cmp; this <= rhs) }
+	fn ">" (this, rhs) { binary_oper!(cmp; this > rhs) }
+	fn ">=" (this, rhs) { binary_oper!(cmp; this >= rhs) }
+
+	fn "<=>" (this, rhs) {
+		let ordering = match Operand::from_object(rhs)? {
+			Operand::Exact(rhs) => Some(this.cmp(&rhs)),
+			Operand::Inexact(rhs) => this.to_f64().partial_cmp(&rhs)
+		};
+
+		match ordering {
+			Some(Ordering::Less) => (-1).into_object(),
+			Some(Ordering::Equal) => 0.into_object(),
+			Some(Ordering::Greater) => 1.into_object(),
+			None => Object::new_null()
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::Rational;
+	use num_bigint::BigInt;
+
+	fn rational(numer: i64, denom: i64) -> Rational {
+		Rational::new(BigInt::from(numer), BigInt::from(denom)).unwrap()
+	}
+
+	#[test]
+	fn normalizes() {
+		assert_eq!(rational(6, 8).to_string(), "3/4");
+		assert_eq!(rational(3, -4).to_string(), "-3/4");
+		assert_eq!(rational(4, 2).to_string(), "2/1");
+		assert!(Rational::new(BigInt::from(1), BigInt::from(0)).is_none());
+	}
+
+	#[test]
+	fn to_number() {
+		assert_eq!(rational(4, 2).to_number().to_string(), "2");
+		assert_eq!(rational(1, 4).to_number().to_string(), "0.25");
+	}
+
+	#[test]
+	fn pow() {
+		assert_eq!(rational(2, 3).pow(2).unwrap(), rational(4, 9));
+		assert_eq!(rational(2, 3).pow(-1).unwrap(), rational(3, 2));
+		assert_eq!(rational(-2, 3).pow(-3).unwrap(), rational(-27, 8));
+		assert_eq!(rational(0, 1).pow(-1), Err("division by zero"));
+		assert_eq!(rational(3, 2).pow(2_000_000_000), Err("result is too large"));
+		assert_eq!(rational(1, 3).pow(-2_000_000_000), Err("result is too large"));
+	}
+}
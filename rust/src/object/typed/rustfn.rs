@@ -1,5 +1,6 @@
 use super::TypedObject;
 use crate::{Object, Result};
+use crate::env::trace::{self, Frame};
 use lazy_static::lazy_static;
 use std::hash::{Hash, Hasher};
 use std::fmt::{self, Debug, Display, Formatter};
@@ -19,7 +20,8 @@ impl RustFn {
 	}
 
 	pub fn call(&self, args: &[&Object]) -> Result<Object> {
-		(self.func)(args)
+		let _frame = Frame::RustFn(self.name).enter();
+		(self.func)(args).map_err(|err| err.traced(trace::current()))
 	}
 }
 
@@ -1,6 +1,7 @@
 use std::fmt::{self, Debug, Display, Formatter};
 use lazy_static::lazy_static;
-use crate::{Error, Result};
+use crate::{Object, Error, Result};
+use std::ops::Range;
 
 #[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
 pub struct Text(String);
@@ -13,6 +14,43 @@ impl Text {
 	pub fn into_inner(self) -> String {
 		self.0
 	}
+
+	/// The number of characters (not bytes) in this text
+	pub fn char_len(&self) -> usize {
+		self.0.chars().count()
+	}
+
+	// the byte offset of the `index`th character; `index` can be the length
+	fn byte_offset(&self, index: usize) -> usize {
+		self.0.char_indices().nth(index).map(|(offset, _)| offset).unwrap_or_else(|| self.0.len())
+	}
+
+	// the bytes of the characters from `start` up to `stop`, which are clamped like `normalize_bound`
+	fn byte_range(&self, start: isize, stop: isize) -> Range<usize> {
+		let len = self.char_len();
+		let start = super::normalize_bound(start, len);
+		let stop = super::normalize_bound(stop, len).max(start);
+		self.byte_offset(start)..self.byte_offset(stop)
+	}
+
+	// the bytes of the `index`th character, if it's in bounds
+	fn char_range(&self, index: isize) -> Option<Range<usize>> {
+		super::normalize_index(index, self.char_len()).map(|index| self.byte_offset(index)..self.byte_offset(index + 1))
+	}
+}
+
+// `this` in `impl_type!` is a copy, so anything that changes text has to go through the object itself
+fn with_text_mut<T, F: FnOnce(&mut String) -> T>(obj: &Object, func: &'static str, body: F) -> Result<T> {
+	let mut map = obj.map().write();
+	match map.downcast_mut::<super::TypedObject>().map(|typed| &mut typed.data) {
+		Some(super::Types::Text(Text(ref mut text))) => Ok(body(text)),
+		_ => Err(Error::BadArgument {
+			func,
+			msg: "only text can be changed",
+			position: 0,
+			obj: obj.clone()
+		})
+	}
 }
 
 impl Display for Text {
@@ -127,6 +165,15 @@ impl_type! { for Text, downcast_fn=downcast_text;
 		(this == rhs.into_text()?).into_object()
 	}
 
+	// texts are ordered by their bytes, which is the same as by their code points
+	fn "<=>" (this, rhs) {
+		match this.0.cmp(&rhs.into_text()?.0) {
+			std::cmp::Ordering::Less => (-1).into_object(),
+			std::cmp::Ordering::Equal => 0.into_object(),
+			std::cmp::Ordering::Greater => 1.into_object()
+		}
+	}
+
 	fn "()" (this) {
 		exec_shell(this.0)?.into_object()
 	}
@@ -140,7 +187,7 @@ impl_type! { for Text, downcast_fn=downcast_text;
 	}
 
 	fn "*" (this, rhs) {
-		let lim = *rhs.into_num()?.into_integer().as_ref() as isize;
+		let lim = isize::from(rhs.into_num()?.into_integer());
 		if lim < 0 {
 			return Ok("".to_string().into_object());
 		}
@@ -154,13 +201,247 @@ impl_type! { for Text, downcast_fn=downcast_text;
 	}
 
 	fn "len" (this) {
-		this.0.len().into_object()
+		this.char_len().into_object()
+	}
+
+	fn "@iter" (this) {
+		let chars = this.0.chars().map(|chr| chr.to_string().into_object()).collect::<Vec<_>>();
+		Iter::new(chars.into_iter()).into_object()
+	}
+
+	fn "chars" (this) {
+		this.0.chars().map(|chr| chr.to_string().into_object()).collect::<Vec<_>>().into_object()
+	}
+
+	fn "bytes" (this) {
+		this.0.bytes().map(IntoObject::into_object).collect::<Vec<_>>().into_object()
+	}
+
+	// `get(i)` is the `i`th character, and `get(start, stop)` is the characters from `start` up to
+	// (but not including) `stop`. negative indices count back from the end.
+	fn "get" (this, index) args {
+		let start = to_index(index, function!(), 1)?;
+
+		if let Some(stop) = args.get(2) {
+			let range = this.byte_range(start, to_index(stop, function!(), 2)?);
+			this.0[range].to_string().into_object()
+		} else {
+			this.char_range(start)
+				.map(|range| this.0[range].to_string().into_object())
+				.unwrap_or_else(Object::new_null)
+		}
+	}
+
+	// `set(i, text)` replaces the `i`th character, and `set(start, stop, text)` replaces a range.
+	fn "set" (@this, index, stop_or_val) args {
+		let text = this.downcast_text().ok_or_else(|| BadArgument {
+			func: function!(),
+			msg: "only text can be changed",
+			position: 0,
+			obj: this.clone()
+		})?;
+		let start = to_index(index, function!(), 1)?;
+
+		let (range, val) = if let Some(val) = args.get(3) {
+			(text.byte_range(start, to_index(stop_or_val, function!(), 2)?), val.into_text()?)
+		} else {
+			let range = text.char_range(start).ok_or_else(|| BadArgument {
+				func: function!(),
+				msg: "index out of bounds",
+				position: 1,
+				obj: index.clone()
+			})?;
+			(range, stop_or_val.into_text()?)
+		};
+
+		with_text_mut(this, function!(), |text| text.replace_range(range, &val.0))?;
+		this.clone()
 	}
 
-	fn "get" (_this, _index) { todo!() }
-	fn "set" (_this, _index, _val) { todo!() }
-	fn "has" (_this, _index) { todo!() }
-	fn "del" (_this, _index) { todo!() }
+	// like `get`, except the characters are removed from the text
+	fn "del" (@this, index) args {
+		let text = this.into_text()?;
+		let start = to_index(index, function!(), 1)?;
+
+		let range = if let Some(stop) = args.get(2) {
+			text.byte_range(start, to_index(stop, function!(), 2)?)
+		} else {
+			match text.char_range(start) {
+				Some(range) => range,
+				None => return Ok(Object::new_null())
+			}
+		};
+
+		with_text_mut(this, function!(), |text| text.drain(range).collect::<String>())?.into_object()
+	}
+
+	fn "has" (this, needle) {
+		this.0.contains(needle.into_text()?.0.as_str()).into_object()
+	}
+
+	// the character index of the first `needle` at or after `start`, or `null` if there isn't one
+	fn "index" (this, needle; start=0.into_object()) {
+		let offset = this.byte_offset(normalize_bound(to_index(&start, function!(), 2)?, this.char_len()));
+
+		match this.0[offset..].find(needle.into_text()?.0.as_str()) {
+			Some(pos) => this.0[..offset + pos].chars().count().into_object(),
+			None => Object::new_null()
+		}
+	}
+
+	fn "replace" (this, from, to) {
+		this.0.replace(from.into_text()?.0.as_str(), &to.into_text()?.0).into_object()
+	}
+
+	// see `format.rs` for the mini-language
+	fn "format" (@this) args {
+		super::format::format(function!(), this, &args[1..])?.into_object()
+	}
+
+	// like `@text`, but as a literal that would parse back into the same text
+	fn "@repr" (this) {
+		let mut repr = String::with_capacity(this.0.len() + 2);
+		repr.push('"');
+
+		let mut chars = this.0.chars().peekable();
+		while let Some(chr) = chars.next() {
+			match chr {
+				'"' => repr.push_str("\\\""),
+				'\\' => repr.push_str("\\\\"),
+				'\n' => repr.push_str("\\n"),
+				'\t' => repr.push_str("\\t"),
+				'\r' => repr.push_str("\\r"),
+				'$' if chars.peek() == Some(&'{') => repr.push_str("\\$"),
+				chr if chr.is_control() => repr.push_str(&format!("\\u{{{:x}}}", chr as u32)),
+				chr => repr.push(chr)
+			}
+		}
+
+		repr.push('"');
+		repr.into_object()
+	}
+
+	fn "upper" (this) { this.0.to_uppercase().into_object() }
+	fn "lower" (this) { this.0.to_lowercase().into_object() }
+
+	// without `chars`, whitespace is stripped
+	fn "strip" (this; chars=Object::new_null()) {
+		if chars.is_null() {
+			this.0.trim().to_string().into_object()
+		} else {
+			let chars = chars.into_text()?.0.chars().collect::<Vec<_>>();
+			this.0.trim_matches(chars.as_slice()).to_string().into_object()
+		}
+	}
+
+	fn "lstrip" (this; chars=Object::new_null()) {
+		if chars.is_null() {
+			this.0.trim_start().to_string().into_object()
+		} else {
+			let chars = chars.into_text()?.0.chars().collect::<Vec<_>>();
+			this.0.trim_start_matches(chars.as_slice()).to_string().into_object()
+		}
+	}
+
+	fn "rstrip" (this; chars=Object::new_null()) {
+		if chars.is_null() {
+			this.0.trim_end().to_string().into_object()
+		} else {
+			let chars = chars.into_text()?.0.chars().collect::<Vec<_>>();
+			this.0.trim_end_matches(chars.as_slice()).to_string().into_object()
+		}
+	}
+
+	// without `sep`, splits on runs of whitespace; an empty `sep` splits into characters
+	fn "split" (this; sep=Object::new_null()) {
+		let parts: Vec<String> = if sep.is_null() {
+			this.0.split_whitespace().map(String::from).collect()
+		} else {
+			let sep = sep.into_text()?.0;
+			if sep.is_empty() {
+				this.0.chars().map(String::from).collect()
+			} else {
+				this.0.split(sep.as_str()).map(String::from).collect()
+			}
+		};
+
+		parts.into_iter().map(IntoObject::into_object).collect::<Vec<_>>().into_object()
+	}
+
+	fn "lines" (this) {
+		this.0.lines().map(|line| line.to_string().into_object()).collect::<Vec<_>>().into_object()
+	}
+
+	fn "starts_with" (this, prefix) {
+		this.0.starts_with(prefix.into_text()?.0.as_str()).into_object()
+	}
+
+	fn "ends_with" (this, suffix) {
+		this.0.ends_with(suffix.into_text()?.0.as_str()).into_object()
+	}
+
+	fn "reverse" (this) {
+		this.0.chars().rev().collect::<String>().into_object()
+	}
+
+	fn "repeat" (@this, amount) {
+		this.call_attr("*", &[amount])?
+	}
+
+	fn "pad_left" (this, width; fill=" ".to_string().into_object()) {
+		let (left, right) = padding(&this, width, &fill, function!())?;
+		format!("{}{}", left + &right, this.0).into_object()
+	}
+
+	fn "pad_right" (this, width; fill=" ".to_string().into_object()) {
+		let (left, right) = padding(&this, width, &fill, function!())?;
+		format!("{}{}", this.0, left + &right).into_object()
+	}
+
+	// any padding that can't be split evenly goes on the right
+	fn "center" (this, width; fill=" ".to_string().into_object()) {
+		let (left, right) = padding(&this, width, &fill, function!())?;
+		format!("{}{}{}", left, this.0, right).into_object()
+	}
+
+	// counts non-overlapping occurrences of `needle`
+	fn "count" (this, needle) {
+		this.0.matches(needle.into_text()?.0.as_str()).count().into_object()
+	}
+
+	// joins each element's `@text`, with this text between them
+	fn "join" (this, list) {
+		list.into_list()?
+			.into_inner()
+			.iter()
+			.map(|obj| obj.into_text().map(Text::into_inner))
+			.collect::<Result<Vec<_>>>()?
+			.join(&this.0)
+			.into_object()
+	}
+}
+
+// the padding needed to make `text` `width` characters long, split in half. `fill` has to be
+// a single character, so the result is always exactly `width` long.
+fn padding(text: &Text, width: &Object, fill: &Object, func: &'static str) -> Result<(String, String)> {
+	let width = super::to_index(width, func, 1)?;
+	let fill_text = fill.into_text()?.0;
+	let mut fill_chars = fill_text.chars();
+
+	let fill_char = match (fill_chars.next(), fill_chars.next()) {
+		(Some(chr), None) => chr,
+		_ => return Err(Error::BadArgument {
+			func,
+			msg: "fill must be exactly one character",
+			position: 2,
+			obj: fill.clone()
+		})
+	};
+
+	let amount = (width.max(0) as usize).saturating_sub(text.char_len());
+	let left = std::iter::repeat(fill_char).take(amount / 2).collect::<String>();
+	let right = std::iter::repeat(fill_char).take(amount - amount / 2).collect::<String>();
+	Ok((left, right))
 }
 
 #[cfg(test)]
@@ -183,6 +464,16 @@ mod rust_tests {
 		assert_eq!(String::from(Text::new("my contents")), "my contents".to_string());
 	}
 
+	#[test]
+	fn null_stop_is_not_ignored() {
+		let text = "hello".to_string().into_object();
+		let null = crate::Object::new_null();
+
+		assert!(text.call_attr("set", &[&0.into_object(), &2.into_object(), &null]).is_err());
+		assert!(text.call_attr("del", &[&0.into_object(), &null]).is_err());
+		assert_eq!(text, "hello".to_string().into_object());
+	}
+
 	#[test]
 	fn create_string_object() {
 		assert_eq!("hi friend".to_string().into_object().downcast_text().unwrap(), Text::new("hi friend"));
@@ -197,6 +488,40 @@ mod rust_tests {
 	fn object_eql_works() {
 		assert_eq!("hi there".to_string().into_object(), "hi there".to_string().into_object());
 	}
+
+	#[test]
+	fn char_len_counts_characters() {
+		assert_eq!(Text::new("héllo").char_len(), 5);
+		assert_eq!(Text::new("").char_len(), 0);
+	}
+
+	#[test]
+	fn char_ranges() {
+		let text = Text::new("naïve");
+		assert_eq!(text.char_range(2).map(|range| &text.0[range]), Some("ï"));
+		assert_eq!(text.char_range(-1).map(|range| &text.0[range]), Some("e"));
+		assert_eq!(text.char_range(5), None);
+	}
+
+	#[test]
+	fn byte_ranges() {
+		let text = Text::new("naïve");
+		assert_eq!(&text.0[text.byte_range(1, 4)], "aïv");
+		assert_eq!(&text.0[text.byte_range(-3, 100)], "ïve");
+		assert_eq!(&text.0[text.byte_range(4, 1)], "");
+	}
+
+	#[test]
+	fn padding() {
+		let pad = |text: &str, width: i32, fill: &str| super::padding(
+			&Text::new(text), &width.into_object(), &fill.to_string().into_object(), "Text::pad"
+		).map_err(|err| err.to_string());
+
+		assert_eq!(pad("ab", 5, "*"), Ok(("*".to_string(), "**".to_string())));
+		assert_eq!(pad("é", 3, " "), Ok((" ".to_string(), " ".to_string())));
+		assert_eq!(pad("abc", 2, " "), Ok((String::new(), String::new())));
+		assert!(pad("ab", 5, "**").is_err());
+	}
 }
 
 
@@ -130,7 +130,13 @@ impl_type! { for Variable, downcast_fn=downcast_var;
 		(this == rhs.into_var()?).into_object()
 	}
 
-	fn "()" (@this) { env().get(this).unwrap_or_else(Object::new_null) }
+	fn "()" (@this) {
+		let value = env().get(this).unwrap_or_else(Object::new_null);
+		if value.is_block() {
+			crate::env::trace::name_callee(&value, this.downcast_var().expect("var downcast failed").0);
+		}
+		value
+	}
 	fn "=" (@this, rhs) { env().set(this.clone(), rhs.clone()); rhs.clone() }
 	fn "<-" (@this, rhs) { env().set(this.clone(), rhs.clone()); rhs.clone() }
 	fn "~" (@this) { env().del(this).unwrap_or_else(Object::new_null) }
@@ -2,7 +2,7 @@ mod parser;
 mod parsable;
 mod result;
 
-pub use self::parser::Parser;
+pub use self::parser::{Parser, Location};
 pub use self::parsable::{Parsable, ParseFromStr, ParseOk};
 pub use self::result::Result;
 
@@ -17,10 +17,19 @@ pub fn parse_str<T: Into<String>>(text: T, parent: Option<Shared<Environment>>)
 	parse(Parser::from_str(text.into()), parent)
 }
 
-fn parse(parser: Parser, parent: Option<Shared<Environment>>) -> crate::Result<Object> {
-	let env = Environment::execute(
+/// Like `parse_file`, but returns the environment the file ran in, so its locals can be used.
+pub fn execute_file<P: AsRef<Path>>(path: P, parent: Option<Shared<Environment>>) -> crate::Result<Shared<Environment>> {
+	execute(Parser::from_file(path.as_ref()).map_err(crate::err::Error::IoError)?, parent)
+}
+
+fn execute(parser: Parser, parent: Option<Shared<Environment>>) -> crate::Result<Shared<Environment>> {
+	Environment::execute(
 		Environment::_new_default_with_stream_using_parent_stack(Shared::new(parser), parent)
-	)?;
+	)
+}
+
+fn parse(parser: Parser, parent: Option<Shared<Environment>>) -> crate::Result<Object> {
+	let env = execute(parser, parent)?;
 	let res = env.read().stack.write().pop().ok_or_else(|| crate::err::Error::NothingToReturn);
 	drop(env);
 	res
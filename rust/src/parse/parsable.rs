@@ -9,6 +9,7 @@ mod whitespace;
 mod forced_eof;
 mod comments;
 mod number;
+mod rational;
 mod variable;
 mod text;
 mod oper;
@@ -29,6 +30,11 @@ pub trait Named {
 pub trait ParseFromStr : Sized {
 	type Err: std::error::Error + 'static;
 	fn from_str(inp: &str) -> Result<ParseOk<Self>, Self::Err>;
+
+	/// How many chars into the text `err` happened, so that it's reported at the right place
+	fn error_offset(_err: &Self::Err) -> usize {
+		0
+	}
 }
 
 pub enum ParseOk<T> {
@@ -63,7 +69,8 @@ impl<T: ParseFromStr + Named + crate::object::IntoObject> Parsable for T {
 			},
 			Err(err) => {
 				warn!(target: "parser", "{} parsing caused an error. err={:?}", Self::NAME, err);
-				parse::Result::Err(crate::Error::Boxed(Box::new(err)))
+				let location = parser.read().location().offset(Self::error_offset(&err));
+				parse::Result::Err(crate::Error::Boxed(Box::new(err)).located(location))
 			}
 		}
 	}
@@ -76,8 +83,9 @@ lazy_static! {
 		ParsableStruct::new::<whitespace::Whitespace>(),
 		ParsableStruct::new::<forced_eof::ForcedEof>(),
 		ParsableStruct::new::<comments::Comments>(),
+		ParsableStruct::new::<rational::Rational>(), // before numbers, so `3/4r` isn't `3 / 4r`
 		ParsableStruct::new::<number::Number>(),
-		ParsableStruct::new::<text::Text>(),
+		ParsableStruct::new::<text::TextLiteral>(),
 		ParsableStruct::new::<oper::Oper>(),
 		ParsableStruct::new::<variable::Variable>(),
 		ParsableStruct::new::<block::Block>(),
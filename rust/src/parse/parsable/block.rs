@@ -9,6 +9,68 @@ use crate::object::typed::block::Parens;
 
 named!(Block);
 
+impl Block {
+	/// Whether `chr` can be the last character of an expression, ie whether a `[` right after it
+	/// is an index rather than a list literal.
+	pub fn ends_expression(chr: char) -> bool {
+		chr.is_alphanumeric() || "_`'\")]}".contains(chr)
+	}
+
+	/// Finds the parens in `text` that are opened but never closed, innermost last.
+	///
+	/// Strings, quoted variables and comments are skipped over. If a closing paren doesn't match
+	/// the last opened one, the text can never be completed, so nothing is returned and the
+	/// parser is left to report the error.
+	pub fn unclosed_parens(text: &str) -> Vec<Parens> {
+		let mut open = Vec::new();
+		let mut chars = text.chars().peekable();
+		let mut prev = None;
+
+		while let Some(chr) = chars.next() {
+			match chr {
+				// raw text doesn't have escapes, so just skip to the closing quote
+				'r' if !prev.map(|c: char| c.is_alphanumeric() || c == '_').unwrap_or(false)
+						&& (chars.peek() == Some(&'"') || chars.peek() == Some(&'\'')) => {
+					let quote = chars.next();
+					chars.by_ref().find(|&c| Some(c) == quote);
+				},
+				'\'' | '"' | '`' => {
+					while let Some(next) = chars.next() {
+						if next == '\\' {
+							chars.next();
+						} else if next == chr {
+							break
+						}
+					}
+				},
+				'#' => { chars.by_ref().find(|&c| c == '\n'); },
+				'/' if chars.peek() == Some(&'/') => { chars.by_ref().find(|&c| c == '\n'); },
+				'/' if chars.peek() == Some(&'*') => {
+					chars.next();
+					let mut prev = None;
+					while let Some(next) = chars.next() {
+						if prev == Some('*') && next == '/' {
+							break
+						}
+						prev = Some(next);
+					}
+				},
+				other => if let Some(paren) = Parens::try_from_start(other) {
+					open.push(paren);
+				} else if let Some(paren) = Parens::try_from_end(other) {
+					if open.pop() != Some(paren) {
+						return Vec::new();
+					}
+				}
+			}
+
+			prev = Some(chr);
+		}
+
+		open
+	}
+}
+
 
 impl Parsable for Block {
 	fn try_parse(parser: &Shared<Parser>) -> parse::Result<Object> {
@@ -27,6 +89,9 @@ impl Parsable for Block {
 			return parse::Result::Eof;
 		}
 
+		// a `[` directly after an expression indexes it; otherwise it's a list literal
+		let is_index = first == '[' && parser.read().previous().map(Block::ends_expression).unwrap_or(false);
+
 		// if it's a starting paren, go until we hit eof
 		let paren = if let Some(paren) = Parens::try_from_start(first) {
 			let x = parser.write().advance(1);
@@ -36,8 +101,8 @@ impl Parsable for Block {
 			return parse::Result::None;
 		};
 
-		let mut block = String::new();
-		let old_chars = parser.read().location().chars;
+		let location = parser.read().location().clone();
+		let old_chars = location.chars;
 		let data = { parser.read().as_ref().to_owned() }; // this is so inefficient right here
 
 		loop {
@@ -54,7 +119,9 @@ impl Parsable for Block {
 			body.pop();
 		}
 
-		parse::Result::Ok(Block::new(paren, body).into_object())
+		// blocks close over the environment that's running when they're parsed
+		let block = Block::new_at(paren, body, location).capture(crate::Environment::current());
+		parse::Result::Ok(if is_index { block.into_index() } else { block }.into_object())
 		// for chr in chars {
 		// 	if chr == '\\' {
 		// 		block 
@@ -73,3 +140,31 @@ impl Parsable for Block {
 	// }
 	}
 }
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn square_blocks(text: &str) -> Vec<bool> {
+		let parser = Shared::new(Parser::from_str(text.to_string()));
+		let mut indexes = Vec::new();
+
+		while let Some(obj) = Parser::next_unevaluated_object(&parser) {
+			if let Some(block) = obj.expect("couldn't parse").downcast_block() {
+				indexes.push(block.is_index());
+			}
+		}
+
+		indexes
+	}
+
+	#[test]
+	fn literals_and_indexes() {
+		assert_eq!(square_blocks("[1, 2]"), vec![false]);
+		assert_eq!(square_blocks("x[0]"), vec![true]);
+		assert_eq!(square_blocks("x [0]"), vec![false]);
+		assert_eq!(square_blocks("(x)[0][1]"), vec![false, true, true]);
+		assert_eq!(square_blocks("a = [[1], 2][0]"), vec![false, true]);
+		assert_eq!(square_blocks("f:([1], 2)"), vec![false]);
+	}
+}
@@ -1,6 +1,6 @@
 use std::str::Chars;
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct CharIter<'a> {
 	iter: Chars<'a>,
 	chars_count: usize,
@@ -3,6 +3,7 @@ use crate::parse::{self, Parser};
 use crate::parse::parsable::{ParseFromStr, ParseOk, Named};
 use std::error::Error;
 use std::fmt::{self, Display, Formatter};
+use num_bigint::BigInt;
 
 pub use crate::object::typed::Number;
 
@@ -16,31 +17,108 @@ named!(Number);
 
 impl ParseFromStr for Number {
 	type Err = NumberParseError;
+
+	/// Parses decimal numbers (with optional fractions and exponents) and `0x`, `0o` and `0b`
+	/// prefixed integers. `_`s can be used anywhere after the first digit as separators.
+	///
+	/// Numbers without a fraction or exponent are parsed exactly, no matter how large they are.
 	fn from_str(text: &str) -> Result<ParseOk<Number>, NumberParseError> {
-		// For now, this can only parse whole numbers. Also, no hexadecimal or stuff
-		let mut chars = text.chars();
-		let mut number = if let Some(number) = chars.next().and_then(|x| x.to_digit(10)) { // radix=10
-			number
-		} else {
-			return Ok(ParseOk::NotFound)
+		use self::NumberParseError::*;
+
+		// numbers are ascii, so we only need to look at as much as could possibly be a number
+		let chars = text.chars()
+			.take_while(|c| c.is_alphanumeric() || *c == '_' || *c == '.' || *c == '+' || *c == '-')
+			.collect::<Vec<char>>();
+
+		if !chars.first().map(|c| c.is_digit(10)).unwrap_or(false) {
+			return Ok(ParseOk::NotFound);
+		}
+
+		let radix = match (chars[0], chars.get(1)) {
+			('0', Some('x')) | ('0', Some('X')) => 16,
+			('0', Some('o')) | ('0', Some('O')) => 8,
+			('0', Some('b')) | ('0', Some('B')) => 2,
+			('0', Some(&radix)) if radix.is_alphabetic() && radix != 'e' && radix != 'E' =>
+				return Err(BadRadix { pos: 1, radix }),
+			_ => 10
 		};
 
-		let mut count = 1;
-
-		for chr in chars {
-			if let Some(digit) = chr.to_digit(10) {
-				number = number * 10 + digit;
-			} else if chr == '_' {
-				/* do nothing */ 
-			} else if chr.is_alphabetic() {
-				return Err(NumberParseError::InvalidSuffix { pos: count, suffix: chr })
-			} else {
-				break;
+		let mut pos = if radix == 10 { 0 } else { 2 };
+		let mut digits = String::new();
+
+		// pushes every digit (skipping `_`s) starting at `pos`, returning whether any were found
+		let push_digits = |pos: &mut usize, digits: &mut String, radix: u32| {
+			let start = digits.len();
+			while let Some(&chr) = chars.get(*pos) {
+				if chr.is_digit(radix) {
+					digits.push(chr);
+				} else if chr != '_' {
+					break;
+				}
+				*pos += 1;
 			}
-			count += 1;
+			digits.len() != start
+		};
+
+		if radix != 10 {
+			if !push_digits(&mut pos, &mut digits, radix) {
+				return Err(BadRadix { pos: 1, radix: chars[1] });
+			}
+
+			if let Some(&suffix) = chars.get(pos).filter(|c| c.is_alphanumeric()) {
+				return Err(InvalidSuffix { pos, suffix });
+			}
+
+			let number = BigInt::parse_bytes(digits.as_bytes(), radix).expect("digits are always valid");
+			return Ok(ParseOk::Found(Number::from(number), pos));
+		}
+
+		push_digits(&mut pos, &mut digits, 10);
+		let mut is_integer = true;
+
+		// a `.` is only a decimal point if a digit follows it; otherwise it's `1.foo`
+		if chars.get(pos) == Some(&'.') && chars.get(pos + 1).map(|c| c.is_digit(10)).unwrap_or(false) {
+			digits.push('.');
+			is_integer = false;
+			pos += 1;
+			push_digits(&mut pos, &mut digits, 10);
+		}
+
+		if let Some(&exp) = chars.get(pos).filter(|&&c| c == 'e' || c == 'E') {
+			let mut exp_pos = pos + 1;
+			let mut exponent = String::from("e");
+
+			if let Some(&sign) = chars.get(exp_pos).filter(|&&c| c == '+' || c == '-') {
+				exponent.push(sign);
+				exp_pos += 1;
+			}
+
+			if !push_digits(&mut exp_pos, &mut exponent, 10) {
+				return Err(InvalidSuffix { pos, suffix: exp });
+			}
+
+			digits.push_str(&exponent);
+			is_integer = false;
+			pos = exp_pos;
+		}
+
+		if let Some(&suffix) = chars.get(pos).filter(|c| c.is_alphabetic()) {
+			return Err(InvalidSuffix { pos, suffix });
 		}
 
-		Ok(ParseOk::Found(Number::new(number as f64), count))
+		let number = if is_integer {
+			Number::from(BigInt::parse_bytes(digits.as_bytes(), 10).expect("digits are always valid"))
+		} else {
+			Number::new(digits.parse::<f64>().expect("digits are always a valid float"))
+		};
+
+		Ok(ParseOk::Found(number, pos))
+	}
+
+	fn error_offset(err: &NumberParseError) -> usize {
+		match err {
+			NumberParseError::InvalidSuffix { pos, .. } | NumberParseError::BadRadix { pos, .. } => *pos
+		}
 	}
 }
 
@@ -61,3 +139,65 @@ impl Error for NumberParseError {
 		}
 	}
 }
+
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn parse(text: &str) -> (Number, usize) {
+		match Number::from_str(text).expect("couldn't parse number") {
+			ParseOk::Found(num, len) => (num, len),
+			ParseOk::NotFound => panic!("no number found in {:?}", text)
+		}
+	}
+
+	fn int(text: &str) -> Number {
+		Number::from(text.parse::<BigInt>().unwrap())
+	}
+
+	#[test]
+	fn whole_numbers() {
+		assert_eq!(parse("123"), (Number::from(123), 3));
+		assert_eq!(parse("1_000_000;"), (Number::from(1_000_000), 9));
+		assert_eq!(parse("99999999999999999999"), (int("99999999999999999999"), 20));
+		assert_eq!(parse("9007199254740993").0.to_string(), "9007199254740993");
+	}
+
+	#[test]
+	fn fractions_and_exponents() {
+		assert_eq!(parse("1.5"), (Number::new(1.5), 3));
+		assert_eq!(parse("1e3"), (Number::new(1000.0), 3));
+		assert_eq!(parse("2.5E-2 "), (Number::new(0.025), 6));
+		assert_eq!(parse("1_0.2_5e+1_0"), (Number::new(10.25e10), 12));
+		assert!(match parse("1.0").0 { Number::Float(_) => true, _ => false });
+	}
+
+	#[test]
+	fn periods_without_digits_arent_fractions() {
+		assert_eq!(parse("1.foo"), (Number::from(1), 1));
+		assert_eq!(parse("1..5"), (Number::from(1), 1));
+	}
+
+	#[test]
+	fn radixes() {
+		assert_eq!(parse("0x1F"), (Number::from(31), 4));
+		assert_eq!(parse("0o17"), (Number::from(15), 4));
+		assert_eq!(parse("0b1010_1010"), (Number::from(170), 11));
+		assert_eq!(parse("0xFFFF_FFFF_FFFF_FFFF_FFFF"), (int("1208925819614629174706175"), 26));
+	}
+	#[test]
+	fn not_found() {
+		assert!(match Number::from_str("foo") { Ok(ParseOk::NotFound) => true, _ => false });
+		assert!(match Number::from_str(".5") { Ok(ParseOk::NotFound) => true, _ => false });
+	}
+
+	#[test]
+	fn errors_have_positions() {
+		assert!(match Number::from_str("0z1") { Err(NumberParseError::BadRadix { pos: 1, radix: 'z' }) => true, _ => false });
+		assert!(match Number::from_str("0x;") { Err(NumberParseError::BadRadix { pos: 1, radix: 'x' }) => true, _ => false });
+		assert!(match Number::from_str("0b102") { Err(NumberParseError::InvalidSuffix { pos: 4, suffix: '2' }) => true, _ => false });
+		assert!(match Number::from_str("12abc") { Err(NumberParseError::InvalidSuffix { pos: 2, suffix: 'a' }) => true, _ => false });
+		assert!(match Number::from_str("1e+") { Err(NumberParseError::InvalidSuffix { pos: 1, suffix: 'e' }) => true, _ => false });
+	}
+}
\ No newline at end of file
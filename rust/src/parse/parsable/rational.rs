@@ -0,0 +1,121 @@
+use crate::parse::parsable::{ParseFromStr, ParseOk, Named};
+use std::error::Error;
+use std::fmt::{self, Display, Formatter};
+use num_bigint::BigInt;
+
+pub use crate::object::typed::Rational;
+
+#[derive(Debug)]
+pub enum RationalParseError {
+	ZeroDenominator { pos: usize }
+}
+
+named!(Rational);
+
+impl ParseFromStr for Rational {
+	type Err = RationalParseError;
+
+	/// Parses rational literals, like `3/4r`. Anything that isn't exactly `<digits>/<digits>r`
+	/// is left for the other parsers, so `3/4` is still division.
+	fn from_str(text: &str) -> Result<ParseOk<Rational>, RationalParseError> {
+		let chars = text.chars().take_while(|c| c.is_alphanumeric() || *c == '_' || *c == '/').collect::<Vec<char>>();
+
+		// reads digits (skipping `_`s after the first one) starting at `pos`
+		let read_digits = |pos: &mut usize| -> Option<BigInt> {
+			if !chars.get(*pos).map(|c| c.is_digit(10)).unwrap_or(false) {
+				return None;
+			}
+
+			let mut digits = String::new();
+			while let Some(&chr) = chars.get(*pos).filter(|c| c.is_digit(10) || **c == '_') {
+				if chr != '_' {
+					digits.push(chr);
+				}
+				*pos += 1;
+			}
+
+			BigInt::parse_bytes(digits.as_bytes(), 10)
+		};
+
+		let mut pos = 0;
+		let numer = match read_digits(&mut pos) {
+			Some(numer) => numer,
+			None => return Ok(ParseOk::NotFound)
+		};
+
+		if chars.get(pos) != Some(&'/') {
+			return Ok(ParseOk::NotFound);
+		}
+
+		pos += 1;
+		let denom_pos = pos;
+		let denom = match read_digits(&mut pos) {
+			Some(denom) => denom,
+			None => return Ok(ParseOk::NotFound)
+		};
+
+		if chars.get(pos) != Some(&'r') || chars.get(pos + 1).map(|c| c.is_alphanumeric() || *c == '_').unwrap_or(false) {
+			return Ok(ParseOk::NotFound);
+		}
+
+		match Rational::new(numer, denom) {
+			Some(rational) => Ok(ParseOk::Found(rational, pos + 1)),
+			None => Err(RationalParseError::ZeroDenominator { pos: denom_pos })
+		}
+	}
+
+	fn error_offset(err: &RationalParseError) -> usize {
+		match err {
+			RationalParseError::ZeroDenominator { pos } => *pos
+		}
+	}
+}
+
+impl Display for RationalParseError {
+	fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+		match self {
+			RationalParseError::ZeroDenominator { .. } => write!(f, "Rational literal has a zero denominator")
+		}
+	}
+}
+
+impl Error for RationalParseError {
+	fn description(&self) -> &str {
+		match self {
+			RationalParseError::ZeroDenominator { .. } => "rational literal has a zero denominator"
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn parse(text: &str) -> Option<(String, usize)> {
+		match Rational::from_str(text).expect("couldn't parse rational") {
+			ParseOk::Found(rational, len) => Some((rational.to_string(), len)),
+			ParseOk::NotFound => None
+		}
+	}
+
+	#[test]
+	fn literals() {
+		assert_eq!(parse("3/4r"), Some(("3/4".to_string(), 4)));
+		assert_eq!(parse("6/8r;"), Some(("3/4".to_string(), 4)));
+		assert_eq!(parse("1_000/3r + 1"), Some(("1000/3".to_string(), 7)));
+	}
+
+	#[test]
+	fn not_rationals() {
+		assert_eq!(parse("3/4"), None);
+		assert_eq!(parse("3/ 4r"), None);
+		assert_eq!(parse("3/4rx"), None);
+		assert_eq!(parse("3/x"), None);
+		assert_eq!(parse("x/4r"), None);
+	}
+
+	#[test]
+	fn zero_denominator() {
+		assert!(match Rational::from_str("1/0r") { Err(RationalParseError::ZeroDenominator { pos: 2 }) => true, _ => false });
+	}
+}
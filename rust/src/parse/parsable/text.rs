@@ -3,22 +3,44 @@ use crate::parse::{self, Parser};
 use crate::parse::parsable::{ParseFromStr, ParseOk, CharIter};
 use std::error::Error;
 use std::fmt::{self, Display, Formatter};
+use std::mem;
 
 pub use crate::object::typed::Text;
+use crate::object::typed::{Interpolation, Segment};
+
+/// A text literal: either plain text, or one containing `${...}`s that have to be evaluated
+pub enum TextLiteral {
+	Plain(Text),
+	Interpolated(Interpolation)
+}
+
+impl IntoObject for TextLiteral {
+	fn into_object(self) -> Object {
+		match self {
+			TextLiteral::Plain(text) => text.into_object(),
+			TextLiteral::Interpolated(interpolation) => interpolation.into_object()
+		}
+	}
+}
 
 #[derive(Debug)]
 pub enum TextParseError {
 	Unterminated,
+	UnterminatedInterpolation,
 	BadHexEscapeSeq(Option<char>, Option<char>),
 	BadEscapeSeq(char),
+	/// A `\u` or `\U` escape that doesn't have the right hex digits; contains what was found
+	BadUnicodeEscapeSeq(String),
+	SurrogateCodePoint(u32),
+	CodePointOutOfRange(u32),
 }
 use self::TextParseError::*;
 
-named!(Text);
+named!(TextLiteral);
 
 fn parse_escape(chars: &mut CharIter<'_>) -> Result<char, TextParseError> {
 	match chars.next().ok_or(Unterminated)? {
-		c @ '\\' | c @ '\'' | c @ '\"' => Ok(c),
+		c @ '\\' | c @ '\'' | c @ '\"' | c @ '$' => Ok(c),
 		'n' => Ok('\n'),
 		't' => Ok('\t'),
 		'r' => Ok('\r'),
@@ -31,15 +53,127 @@ fn parse_escape(chars: &mut CharIter<'_>) -> Result<char, TextParseError> {
 				.and_then(std::char::from_u32)
 				.ok_or_else(|| BadHexEscapeSeq(Some(first), Some(second)))
 		},
-		'u' => unimplemented!("\\u"),
-		'U' => unimplemented!("\\U"),
+		'e' => Ok('\x1b'),
+		'a' => Ok('\x07'),
+		'b' => Ok('\x08'),
+		'f' => Ok('\x0c'),
+		'v' => Ok('\x0b'),
+		'u' => parse_unicode_escape(chars, 4),
+		'U' => parse_unicode_escape(chars, 8),
 		other => Err(BadEscapeSeq(other))
 	}
 }
 
-impl ParseFromStr for Text {
+// parses the rest of `\u{...}` (up to six digits), or `\uXXXX`/`\UXXXXXXXX` (exactly `len` digits)
+fn parse_unicode_escape(chars: &mut CharIter<'_>, len: usize) -> Result<char, TextParseError> {
+	let mut digits = String::new();
+
+	if len == 4 && chars.clone().next() == Some('{') {
+		chars.next();
+		loop {
+			match chars.next() {
+				Some('}') => break,
+				Some(chr) if chr.is_digit(16) && digits.len() < 6 => digits.push(chr),
+				Some(chr) => {
+					digits.push(chr);
+					return Err(BadUnicodeEscapeSeq(format!("{{{}", digits)));
+				},
+				None => return Err(BadUnicodeEscapeSeq(format!("{{{}", digits)))
+			}
+		}
+
+		if digits.is_empty() {
+			return Err(BadUnicodeEscapeSeq("{}".to_string()));
+		}
+	} else {
+		while digits.len() < len {
+			match chars.next() {
+				Some(chr) if chr.is_digit(16) => digits.push(chr),
+				Some(chr) => {
+					digits.push(chr);
+					return Err(BadUnicodeEscapeSeq(digits));
+				},
+				None => return Err(BadUnicodeEscapeSeq(digits))
+			}
+		}
+	}
+
+	let code_point = u32::from_str_radix(&digits, 16).map_err(|_| BadUnicodeEscapeSeq(digits.clone()))?;
+
+	match code_point {
+		0xD800..=0xDFFF => Err(SurrogateCodePoint(code_point)),
+		_ => std::char::from_u32(code_point).ok_or(CodePointOutOfRange(code_point))
+	}
+}
+
+// raw text (`r"..."` or `r'...'`) has no escapes or interpolation, so it can't contain its quote
+fn parse_raw(text: &str) -> Result<ParseOk<TextLiteral>, TextParseError> {
+	let mut chars = CharIter::from(text);
+	chars.next(); // the `r`
+
+	let quote = match chars.next() {
+		Some(q @ '\"') | Some(q @ '\'') => q,
+		_ => return Ok(ParseOk::NotFound)
+	};
+
+	let mut raw = String::new();
+	loop {
+		match chars.next() {
+			Some(q) if q == quote => break,
+			Some(other) => raw.push(other),
+			None => return Err(Unterminated)
+		}
+	}
+
+	Ok(ParseOk::Found(TextLiteral::Plain(Text::from(raw)), chars.chars_count()))
+}
+
+// reads the code of a `${...}` up to (and including) its closing `}`, skipping over any
+// braces inside nested text literals
+fn parse_interpolation(chars: &mut CharIter<'_>) -> Result<String, TextParseError> {
+	let mut code = String::new();
+	let mut depth = 1;
+
+	loop {
+		let chr = chars.next().ok_or(UnterminatedInterpolation)?;
+		match chr {
+			'{' => depth += 1,
+			'}' => {
+				depth -= 1;
+				if depth == 0 {
+					return Ok(code);
+				}
+			},
+			// the closing quote is pushed along with everything else below
+			quote @ '\'' | quote @ '\"' => {
+				code.push(quote);
+				loop {
+					match chars.next().ok_or(UnterminatedInterpolation)? {
+						'\\' => {
+							code.push('\\');
+							code.push(chars.next().ok_or(UnterminatedInterpolation)?);
+						},
+						chr if chr == quote => break,
+						chr => code.push(chr)
+					}
+				}
+			},
+			_ => {}
+		}
+		code.push(chr);
+	}
+}
+
+impl ParseFromStr for TextLiteral {
 	type Err = TextParseError;
-	fn from_str(text: &str) -> Result<ParseOk<Text>, TextParseError> {
+
+	/// Double quoted text can contain `${expr}`, which is replaced by `expr`'s `@text` whenever
+	/// the literal is evaluated. Use `\$` for a literal `$`. Text prefixed with `r` is raw.
+	fn from_str(text: &str) -> Result<ParseOk<TextLiteral>, TextParseError> {
+		if text.starts_with('r') {
+			return parse_raw(text);
+		}
+
 		let mut chars = CharIter::from(text);
 
 		let quote = match chars.next() {
@@ -48,18 +182,39 @@ impl ParseFromStr for Text {
 		};
 
 		debug_assert!(quote == '\'' || quote == '\"', quote);
-		let mut text = String::new();
+		let mut current = String::new();
+		let mut segments = Vec::new();
 
 		loop {
 			match chars.next() {
 				Some(q) if q == quote => break,
-				Some('\\') => text.push(parse_escape(&mut chars)?),
-				Some(other) => text.push(other),
+				Some('\\') => current.push(parse_escape(&mut chars)?),
+				Some('$') if quote == '"' && text[chars.chars_count()..].starts_with('{') => {
+					chars.next(); // the `{`
+					let start = chars.chars_count();
+					let code = parse_interpolation(&mut chars)?;
+
+					if !current.is_empty() {
+						segments.push(Segment::Text(mem::replace(&mut current, String::new())));
+					}
+					segments.push(Segment::Code { code, start });
+				},
+				Some(other) => current.push(other),
 				None => return Err(Unterminated)
 			}
 		}
 
-		Ok(ParseOk::Found(Text::from(text), chars.chars_count()))
+		let len = chars.chars_count();
+
+		if segments.is_empty() {
+			return Ok(ParseOk::Found(TextLiteral::Plain(Text::from(current)), len));
+		}
+
+		if !current.is_empty() {
+			segments.push(Segment::Text(current));
+		}
+
+		Ok(ParseOk::Found(TextLiteral::Interpolated(Interpolation::new(text[..len].to_string(), segments)), len))
 	}
 }
 
@@ -67,11 +222,15 @@ impl Display for TextParseError {
 	fn fmt(&self, f: &mut Formatter) -> fmt::Result {
 		match self {
 			Unterminated => write!(f, "Unterminated string"),
+			UnterminatedInterpolation => write!(f, "Unterminated `${{` in string"),
 			BadEscapeSeq(seq) => write!(f, "Bad escape sequence '\\{}'", seq),
 			BadHexEscapeSeq(None, None) => write!(f, "Missing hex escape seq"),
 			BadHexEscapeSeq(Some(x), None) => write!(f, "Bad hex escape seq '\\x{}'", x),
 			BadHexEscapeSeq(Some(x), Some(y)) => write!(f, "Bad hex escape seq '\\x{}{}'", x, y),
 			BadHexEscapeSeq(None, Some(_)) => unreachable!(),
+			BadUnicodeEscapeSeq(seq) => write!(f, "Bad unicode escape seq '\\u{}'", seq),
+			SurrogateCodePoint(code_point) => write!(f, "Unicode escape is a surrogate: U+{:04X}", code_point),
+			CodePointOutOfRange(code_point) => write!(f, "Unicode escape is out of range: U+{:X}", code_point),
 		}
 	}
 }
@@ -80,8 +239,103 @@ impl Error for TextParseError {
 	fn description(&self) -> &str {
 		match self {
 			Unterminated => "unterminated string",
+			UnterminatedInterpolation => "unterminated interpolation",
 			BadEscapeSeq(_) => "bad escape sequence",
 			BadHexEscapeSeq(_, _) => "bad hex escape sequence",
+			BadUnicodeEscapeSeq(_) => "bad unicode escape sequence",
+			SurrogateCodePoint(_) => "unicode escape is a surrogate",
+			CodePointOutOfRange(_) => "unicode escape is out of range",
 		}
 	}
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn parse(text: &str) -> (TextLiteral, usize) {
+		match TextLiteral::from_str(text).expect("couldn't parse text") {
+			ParseOk::Found(literal, len) => (literal, len),
+			ParseOk::NotFound => panic!("no text found in {:?}", text)
+		}
+	}
+
+	fn segments(text: &str) -> Vec<Segment> {
+		match parse(text).0 {
+			TextLiteral::Interpolated(interpolation) => interpolation.segments().to_vec(),
+			TextLiteral::Plain(text) => panic!("{:?} wasn't interpolated", text)
+		}
+	}
+
+	fn code(code: &str, start: usize) -> Segment {
+		Segment::Code { code: code.to_string(), start }
+	}
+
+	#[test]
+	fn plain_text() {
+		assert!(match parse("\"it took you 3 tries\"") { (TextLiteral::Plain(_), 21) => true, _ => false });
+		assert!(match parse("'${guesses}'") { (TextLiteral::Plain(_), 12) => true, _ => false });
+		assert!(match parse("\"\\${guesses}\"") { (TextLiteral::Plain(_), 13) => true, _ => false });
+		assert!(match parse("\"$guesses\"") { (TextLiteral::Plain(_), 10) => true, _ => false });
+	}
+
+	#[test]
+	fn interpolation() {
+		assert_eq!(segments("\"it took you ${guesses} tries\""), vec![
+			Segment::Text("it took you ".to_string()),
+			code("guesses", 15),
+			Segment::Text(" tries".to_string())
+		]);
+		assert_eq!(segments("\"${a}${b}\""), vec![code("a", 3), code("b", 7)]);
+	}
+
+	#[test]
+	fn nested_braces_and_text() {
+		assert_eq!(segments("\"${ {1}!  }\""), vec![code(" {1}!  ", 3)]);
+		assert_eq!(segments("\"${ \"}\" + \"${x}\" }\""), vec![code(" \"}\" + \"${x}\" ", 3)]);
+	}
+
+	#[test]
+	fn unterminated_interpolation() {
+		assert!(match TextLiteral::from_str("\"${x\"") { Err(UnterminatedInterpolation) => true, _ => false });
+	}
+
+	fn plain(text: &str) -> String {
+		match parse(text).0 {
+			TextLiteral::Plain(text) => text.into_inner(),
+			TextLiteral::Interpolated(_) => panic!("{:?} was interpolated", text)
+		}
+	}
+
+	#[test]
+	fn unicode_escapes() {
+		assert_eq!(plain("\"\\u{1F600}\""), "\u{1F600}");
+		assert_eq!(plain("\"\\u{e9}\""), "é");
+		assert_eq!(plain("\"\\u00e9x\""), "éx");
+		assert_eq!(plain("\"\\U0001F600\""), "\u{1F600}");
+	}
+
+	#[test]
+	fn bad_unicode_escapes() {
+		assert!(match TextLiteral::from_str("\"\\u{D800}\"") { Err(SurrogateCodePoint(0xD800)) => true, _ => false });
+		assert!(match TextLiteral::from_str("\"\\uDFFF\"") { Err(SurrogateCodePoint(0xDFFF)) => true, _ => false });
+		assert!(match TextLiteral::from_str("\"\\u{110000}\"") { Err(CodePointOutOfRange(0x110000)) => true, _ => false });
+		assert!(match TextLiteral::from_str("\"\\U00110000\"") { Err(CodePointOutOfRange(0x110000)) => true, _ => false });
+		assert!(match TextLiteral::from_str("\"\\u12\"") { Err(BadUnicodeEscapeSeq(_)) => true, _ => false });
+		assert!(match TextLiteral::from_str("\"\\u{}\"") { Err(BadUnicodeEscapeSeq(_)) => true, _ => false });
+		assert!(match TextLiteral::from_str("\"\\u{1234567}\"") { Err(BadUnicodeEscapeSeq(_)) => true, _ => false });
+	}
+
+	#[test]
+	fn control_escapes() {
+		assert_eq!(plain("\"\\e\\a\\b\\f\\v\""), "\x1b\x07\x08\x0c\x0b");
+	}
+
+	#[test]
+	fn raw_text() {
+		assert_eq!(plain("r\"C:\\new\\${dir}\""), "C:\\new\\${dir}");
+		assert_eq!(plain("r'say \"hi\"'"), "say \"hi\"");
+		assert!(match TextLiteral::from_str("rx\"\"") { Ok(ParseOk::NotFound) => true, _ => false });
+		assert!(match TextLiteral::from_str("r\"abc") { Err(Unterminated) => true, _ => false });
+	}
+}
@@ -1,6 +1,7 @@
 use crate::{Object, Shared, Result};
 use std::path::{Path, PathBuf};
 use std::{fs, io, sync::Mutex};
+use std::fmt::{self, Display, Formatter};
 use super::parsable::{BUILTIN_PARSERS, ParsableStruct};
 use crate::parse::{self, Parsable};
 
@@ -9,10 +10,12 @@ pub struct Parser {
 	data: String,
 	parsers: Shared<Vec<ParsableStruct>>,
 	location: Location,
+	previous: Option<char>,
 	rollback: Shared<Vec<Object>>
 }
 
-#[derive(Debug, Default, PartialEq, Eq, Clone)]
+/// Where something was parsed. `line` and `col` start at zero, but are displayed starting at one.
+#[derive(Debug, Default, PartialEq, Eq, Clone, Hash)]
 pub struct Location {
 	pub source: Option<PathBuf>,
 	pub line: usize,
@@ -20,6 +23,53 @@ pub struct Location {
 	pub chars: usize
 }
 
+impl Location {
+	/// This location, moved `chars` characters further along the same line
+	pub fn offset(&self, chars: usize) -> Location {
+		Location {
+			col: self.col + chars,
+			chars: self.chars + chars,
+			..self.clone()
+		}
+	}
+
+	/// Where parsing ends up after reading `text` from this location
+	pub fn after(&self, text: &str) -> Location {
+		let newlines = text.matches('\n').count();
+		let (line, col) = if newlines == 0 {
+			(self.line, self.col + text.chars().count())
+		} else {
+			(self.line + newlines, text.rsplit('\n').next().map(|line| line.chars().count()).unwrap_or(0))
+		};
+
+		Location { line, col, chars: self.chars + text.chars().count(), ..self.clone() }
+	}
+
+	/// Renders the line of `source` that this location points to, with a caret under the column.
+	pub fn snippet(&self, source: &str) -> Option<String> {
+		let line = source.lines().nth(self.line)?;
+		let number = (self.line + 1).to_string();
+		let gutter = " ".repeat(number.len());
+		// keep tabs so the caret lines up with the source line
+		let padding = line.chars()
+			.take(self.col)
+			.map(|chr| if chr == '\t' { '\t' } else { ' ' })
+			.collect::<String>();
+
+		Some(format!("{} |\n{} | {}\n{} | {}^", gutter, number, line, gutter, padding))
+	}
+}
+
+impl Display for Location {
+	fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+		match self.source {
+			Some(ref source) => write!(f, "{}", source.display())?,
+			None => write!(f, "<input>")?
+		}
+		write!(f, ":{}:{}", self.line + 1, self.col + 1)
+	}
+}
+
 impl Parser {
 	pub fn from_file(path: &Path) -> io::Result<Parser> {
 		Ok(Parser {
@@ -29,27 +79,38 @@ impl Parser {
 				..Location::default()
 			},
 			parsers: BUILTIN_PARSERS.clone(),
+			previous: None,
 			rollback: Shared::new(Vec::new())
 		})
 	}
 
 	pub fn from_str(data: String) -> Parser {
+		Parser::from_str_at(data, Location::default())
+	}
+
+	/// Creates a parser for `data`, which starts at `location` (eg the body of a block)
+	pub fn from_str_at(data: String, location: Location) -> Parser {
 		Parser {
 			data,
-			location: Location::default(),
+			location,
 			parsers: BUILTIN_PARSERS.clone(),
+			previous: None,
 			rollback: Shared::new(Vec::new())
 		}
 	}
 
 	pub fn advance(&mut self, amount: usize) -> String {
 		let data: String = self.data.drain(..amount).collect();
-		self.location.line += data.lines().count();
-		self.location.col = data.lines().last().map(str::len).unwrap_or(0);
-		self.location.chars += data.chars().count();
+		self.location = self.location.after(&data);
+		self.previous = data.chars().last().or(self.previous);
 		data
 	}
 
+	/// The last character that was advanced over, if any
+	pub fn previous(&self) -> Option<char> {
+		self.previous
+	}
+
 	pub fn beginning(&self) -> String {
 		if self.data.len() < 15 {
 			self.data.clone()
@@ -92,19 +153,23 @@ impl Parser {
 		trace!(target: "parse", "Beginning parse. stream={:?}", parser.read().as_ref());
 
 		let parsers = parser.read().parsers.clone();
+		let location = parser.read().location.clone();
 
 		for parsablefn in parsers.read().iter() {
 			match parsablefn.call(parser) {
 				parse::Result::Restart => return Parser::next_unevaluated_object(parser),
-				parse::Result::Ok(object) => return Some(Ok(object)),
-				parse::Result::Err(err) => return Some(Err(err)),
+				parse::Result::Ok(object) => {
+					object.set_location(location);
+					return Some(Ok(object))
+				},
+				parse::Result::Err(err) => return Some(Err(err.located(location))),
 
 				parse::Result::Eof => return None,
 				parse::Result::None => { /* do nothing */ }
 			}
 		}
 
-		Some(Err(crate::Error::NothingParsableFound(parser.clone())))
+		Some(Err(crate::Error::NothingParsableFound(parser.clone()).located(location)))
 	}
 }
 
@@ -0,0 +1,224 @@
+use crate::{Shared, Object, Error, Environment};
+use crate::collections::{Collection, Mapping};
+use crate::object::typed::Block;
+use crate::parse::Parser;
+use std::io::{self, Write, BufRead};
+use std::path::PathBuf;
+use std::fs::{self, OpenOptions};
+
+const PROMPT: &str = "quest> ";
+const CONTINUATION_PROMPT: &str = "  ...> ";
+const HISTORY_FILE: &str = ".quest_history";
+
+const HELP: &str = "\
+:help          show this message
+:locals        show the variables defined so far (`$locals`)
+:stack         show the current stack (`$stack`)
+:history       show previous entries
+:redo <n>      run entry <n> from `:history` again
+:reset         throw away every variable and start fresh
+:quit          leave the repl (so does `exit()` and end-of-file)";
+
+/// The read-eval-print loop that `quest` starts when it isn't given a script.
+///
+/// Every entry is executed in the same `Environment`, so variables survive between lines.
+pub struct Repl {
+	env: Shared<Environment>,
+	history: Vec<String>,
+	history_file: Option<PathBuf>
+}
+
+// entries are saved one per line, so newlines (and the backslashes used to escape them) are escaped
+fn escape_history(entry: &str) -> String {
+	entry.replace('\\', "\\\\").replace('\n', "\\n")
+}
+
+fn unescape_history(line: &str) -> String {
+	let mut entry = String::with_capacity(line.len());
+	let mut chars = line.chars();
+
+	while let Some(chr) = chars.next() {
+		match (chr, chars.clone().next()) {
+			('\\', Some('n')) => { chars.next(); entry.push('\n'); },
+			('\\', Some('\\')) => { chars.next(); entry.push('\\'); },
+			(chr, _) => entry.push(chr)
+		}
+	}
+
+	entry
+}
+
+impl Default for Repl {
+	fn default() -> Repl {
+		Repl::new()
+	}
+}
+
+impl Repl {
+	pub fn new() -> Repl {
+		let history_file = std::env::var_os("HOME").map(|home| PathBuf::from(home).join(HISTORY_FILE));
+		let history = history_file.as_ref()
+			.and_then(|file| fs::read_to_string(file).ok())
+			.map(|data| data.lines().map(unescape_history).collect())
+			.unwrap_or_default();
+
+		Repl { env: Repl::new_env(), history, history_file }
+	}
+
+	fn new_env() -> Shared<Environment> {
+		Environment::_new_default_with_stream_and_parent(Shared::new(Parser::default()), None)
+	}
+
+	/// Runs until stdin is closed or `exit` is called, returning the exit code.
+	pub fn run(&mut self) -> i32 {
+		let stdin = io::stdin();
+		let mut lines = stdin.lock().lines();
+
+		loop {
+			let entry = match Repl::read_entry(&mut lines) {
+				Ok(Some(entry)) => entry,
+				Ok(None) => return 0,
+				Err(err) => {
+					eprintln!("quest: unable to read input: {}", err);
+					return 1;
+				}
+			};
+
+			if entry.trim().is_empty() {
+				continue;
+			}
+
+			if let Some(code) = self.handle_entry(entry) {
+				return code;
+			}
+		}
+	}
+
+	// reads lines until every paren that was opened has been closed
+	fn read_entry<I: Iterator<Item=io::Result<String>>>(lines: &mut I) -> io::Result<Option<String>> {
+		let mut entry = String::new();
+		let mut prompt = PROMPT;
+
+		loop {
+			print!("{}", prompt);
+			io::stdout().flush()?;
+
+			match lines.next() {
+				Some(line) => entry.push_str(&line?),
+				None if entry.is_empty() => {
+					println!();
+					return Ok(None)
+				},
+				None => return Ok(Some(entry))
+			}
+
+			if Block::unclosed_parens(&entry).is_empty() {
+				return Ok(Some(entry));
+			}
+
+			entry.push('\n');
+			prompt = CONTINUATION_PROMPT;
+		}
+	}
+
+	fn handle_entry(&mut self, entry: String) -> Option<i32> {
+		if entry.trim_start().starts_with(':') {
+			return self.meta_command(entry.trim());
+		}
+
+		self.add_history(entry.clone());
+		self.eval(entry)
+	}
+
+	fn eval(&mut self, code: String) -> Option<i32> {
+		let stack_len = self.env.read().stack.read().len();
+		let result = Environment::execute_parser(self.env.clone(), Shared::new(Parser::from_str(code.clone())));
+
+		match result {
+			Ok(_) | Err(Error::NothingToReturn) => {},
+			Err(Error::Exit(code)) => return Some(code),
+			Err(err) => {
+				eprintln!("{}", err.diagnostic(Some(&code)));
+				return None;
+			}
+		}
+
+		let top = {
+			let stack = self.env.read().stack.read()._to_vec();
+			if stack.len() > stack_len { stack.last().cloned() } else { None }
+		};
+
+		if let Some(top) = top {
+			println!("{}", Repl::to_text(&top));
+		}
+
+		None
+	}
+
+	fn to_text(obj: &Object) -> String {
+		obj.into_text()
+			.map(String::from)
+			.unwrap_or_else(|_| obj.to_string())
+	}
+
+	fn meta_command(&mut self, command: &str) -> Option<i32> {
+		let mut words = command[1..].split_whitespace();
+
+		match words.next().unwrap_or("") {
+			"help" | "h" | "?" => println!("{}", HELP),
+			"quit" | "q" | "exit" => return Some(0),
+			"reset" => {
+				self.env = Repl::new_env();
+				println!("environment reset");
+			},
+			"locals" => match self.env.read().get_attr("$locals").map(|locals| locals.into_map()) {
+				Some(Ok(locals)) => println!("{}", locals.into_inner()),
+				_ => println!("{{}}")
+			},
+			"stack" => println!("{}", self.env.read().stack),
+			"history" => for (i, entry) in self.history.iter().enumerate() {
+				println!("{:>4}  {}", i, entry);
+			},
+			"redo" => match words.next().and_then(|n| n.parse::<usize>().ok()).and_then(|n| self.history.get(n).cloned()) {
+				Some(entry) => {
+					println!("{}{}", PROMPT, entry);
+					return self.handle_entry(entry);
+				},
+				None => eprintln!("usage: :redo <n>, where <n> is from `:history`")
+			},
+			other => eprintln!("unknown command `:{}`; try `:help`", other)
+		}
+
+		None
+	}
+
+	fn add_history(&mut self, entry: String) {
+		if let Some(ref file) = self.history_file {
+			let written = OpenOptions::new()
+				.create(true)
+				.append(true)
+				.open(file)
+				.and_then(|mut file| writeln!(file, "{}", escape_history(&entry)));
+
+			if let Err(err) = written {
+				warn!("unable to save history to {:?}: {}", file, err);
+			}
+		}
+
+		self.history.push(entry);
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn history_round_trips() {
+		for entry in &["x = 1;", "{\n\tx\n}", "disp:[\"a\\nb\"]", "ends with \\", "\\\n"] {
+			let escaped = escape_history(entry);
+			assert!(!escaped.contains('\n'), "{:?} wasn't escaped", escaped);
+			assert_eq!(unescape_history(&escaped), *entry);
+		}
+	}
+}
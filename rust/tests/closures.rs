@@ -0,0 +1,83 @@
+mod common;
+
+use common::{run, nums};
+use quest::IntoObject;
+
+#[test]
+fn counters() {
+	assert_eq!(run("
+		`make_counter` = {
+			`count` = 0;
+			{ $closure.`count` = count + 1 }
+		};
+
+		`counter` = make_counter!;
+		counter!;
+		counter!;
+		[counter!, (make_counter!)!]
+	"), nums(&[3, 1]));
+}
+
+#[test]
+fn sees_later_assignments() {
+	assert_eq!(run("
+		`x` = 1;
+		`get_x` = { x };
+		`x` = 2;
+		get_x!
+	"), 2.into_object());
+}
+
+#[test]
+fn nested_closures() {
+	assert_eq!(run("
+		`adder` = { |a| { |b| { |c| a + b + c } } };
+		((adder:[1]):[2]):[3]
+	"), 6.into_object());
+}
+
+#[test]
+fn returned_closures() {
+	assert_eq!(run("
+		`make_adder` = { |n| { |m| n + m } };
+		`add1` = make_adder:[1];
+		`add10` = make_adder:[10];
+		[add1:[5], add10:[5], add1:[0]]
+	"), nums(&[6, 15, 1]));
+}
+
+#[test]
+fn closure_special() {
+	assert_eq!(run("
+		`marker` = 1;
+		`get_marker` = { $closure::`marker` };
+		get_marker!
+	"), 1.into_object());
+
+	assert!(run("$closure").is_null());
+}
+
+#[test]
+fn closure_attributes_are_locals() {
+	assert_eq!(run("
+		`make_counter` = {
+			`count` = 0;
+			{ $closure.`count` = count + 1 }
+		};
+
+		`env` = (make_counter!).`closure`!;
+		[env.`.?`:[`count`], env.`.~`:[`count`], env.`.?`:[`count`]]
+	"), vec![true.into_object(), 0.into_object(), false.into_object()].into_object());
+
+	assert_eq!(run("
+		`make_counter` = {
+			`count` = 0;
+			{ $closure.`count` = count + 1 }
+		};
+
+		`counter` = make_counter!;
+		`env` = counter.`closure`!;
+		env.`.=`:[`count`, 10];
+		counter!
+	"), 11.into_object());
+}
@@ -0,0 +1,72 @@
+//! Helpers for running Quest code in the integration tests.
+#![allow(dead_code)]
+use quest::{Object, IntoObject};
+use std::sync::{Mutex, MutexGuard};
+use lazy_static::lazy_static;
+
+lazy_static! {
+	// the current environment is global, so scripts can't be run at the same time
+	static ref RUNNING: Mutex<()> = Mutex::new(());
+}
+
+/// Stops other tests from running scripts until it's dropped
+pub fn lock() -> MutexGuard<'static, ()> {
+	RUNNING.lock().unwrap_or_else(|err| err.into_inner())
+}
+
+/// Runs `code`, returning the last thing it left on the stack
+pub fn run(code: &str) -> Object {
+	try_run(code).expect("script failed")
+}
+
+pub fn try_run(code: &str) -> quest::Result<Object> {
+	let _running = lock();
+	quest::parse::parse_str(code, None)
+}
+
+pub fn nums(nums: &[i64]) -> Object {
+	nums.iter().map(|&num| num.into_object()).collect::<Vec<_>>().into_object()
+}
+
+pub fn text(text: &str) -> Object {
+	text.to_string().into_object()
+}
+
+/// Runs the file at `path`, like `quest path` does
+pub fn try_run_file(path: &std::path::Path) -> quest::Result<Object> {
+	let _running = lock();
+	quest::parse::parse_file(path, None)
+}
+
+/// A directory of scripts that's deleted when it's dropped
+pub struct TempDir(std::path::PathBuf);
+
+impl TempDir {
+	pub fn new() -> TempDir {
+		use std::sync::atomic::{AtomicUsize, Ordering};
+		static COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+		let path = std::env::temp_dir().join(format!(
+			"quest-test-{}-{}", std::process::id(), COUNTER.fetch_add(1, Ordering::Relaxed)));
+		std::fs::create_dir_all(&path).expect("couldn't create temp dir");
+		TempDir(path)
+	}
+
+	pub fn path(&self) -> &std::path::Path {
+		&self.0
+	}
+
+	/// Writes `code` to `name` in this directory, creating any directories in between
+	pub fn write(&self, name: &str, code: &str) -> std::path::PathBuf {
+		let path = self.0.join(name);
+		std::fs::create_dir_all(path.parent().expect("file has no parent")).expect("couldn't create dir");
+		std::fs::write(&path, code).expect("couldn't write file");
+		path
+	}
+}
+
+impl Drop for TempDir {
+	fn drop(&mut self) {
+		let _ = std::fs::remove_dir_all(&self.0);
+	}
+}
@@ -0,0 +1,48 @@
+mod common;
+
+use common::{run, text};
+use quest::IntoObject;
+
+#[test]
+fn custom_kinds() {
+	assert_eq!(run("
+		`LookupError` = ErrorKind:[\"LookupError\"];
+		`KeyError` = ErrorKind:[\"KeyError\", LookupError];
+		LookupError.`hint` = \"check the key\";
+
+		try:[{ throw:[Error:[\"no key\", KeyError]] }, { |err|
+			[
+				err.`is`:[LookupError],
+				err.`is`:[\"KeyError\"],
+				err.`is`:[\"Error\"],
+				err.`is`:[\"TypeError\"],
+				err::`hint`,
+				err.`kind`!
+			]
+		}]
+	"), vec![
+		true.into_object(),
+		true.into_object(),
+		true.into_object(),
+		false.into_object(),
+		text("check the key"),
+		text("KeyError")
+	].into_object());
+}
+
+#[test]
+fn builtin_errors_are_kinds_of_error() {
+	assert_eq!(run("
+		`block` = {};
+		try:[{ block.`@list`! }, { |err| [err.`is`:[\"ConversionFailure\"], err.`is`:[\"Error\"]] }]
+	"), vec![true.into_object(), true.into_object()].into_object());
+}
+
+#[test]
+fn thrown_errors_keep_their_attributes() {
+	assert_eq!(run("
+		`err` = Error:[\"oops\"];
+		err.`code` = 42;
+		try:[{ throw:[err] }, { |caught| [caught::`code`, caught === err] }]
+	"), vec![42.into_object(), true.into_object()].into_object());
+}
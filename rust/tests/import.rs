@@ -0,0 +1,146 @@
+mod common;
+
+use common::{try_run_file, nums, TempDir};
+use quest::{Object, Error, IntoObject};
+
+#[test]
+fn relative_to_the_importing_file() {
+	let dir = TempDir::new();
+	// if imports were relative to the main file, this would be found instead
+	dir.write("sibling.qs", "`x` = 0;");
+	dir.write("lib/sibling.qs", "`x` = 41;");
+	dir.write("lib/helper.qs", "`value` = (import:[\"sibling\"])::`x` + 1;");
+	let main = dir.write("main.qs", "(import:[\"lib/helper\"])::`value`");
+
+	assert_eq!(try_run_file(&main).unwrap(), 42.into_object());
+}
+
+#[test]
+fn modules_are_cached_by_canonical_path() {
+	let dir = TempDir::new();
+	// `$module_path` is shared by everything, so it's used to count how often the module runs
+	dir.write("counted.qs", "$module_path.`push`:[\"ran\"];");
+	let main = dir.write("main.qs", "
+		`$module_path` = [];
+		`a` = import:[\"counted\"];
+		`b` = import:[\"./counted.qs\"];
+		`c` = import:[\"lib/../counted\"];
+		`runs` = $module_path.`len`!;
+		`$module_path` = [];
+		[a === b, b === c, runs]
+	");
+	std::fs::create_dir_all(dir.path().join("lib")).unwrap();
+
+	assert_eq!(
+		try_run_file(&main).unwrap(),
+		vec![true.into_object(), true.into_object(), 1.into_object()].into_object()
+	);
+}
+
+#[test]
+fn import_cycles() {
+	let dir = TempDir::new();
+	let a = dir.write("a.qs", "import:[\"b\"];");
+	let b = dir.write("b.qs", "import:[\"a\"];");
+	let main = dir.write("main.qs", "(import:[\"a\"]); (import:[\"b\"])::`x`");
+
+	match try_run_file(&main).unwrap_err().inner() {
+		Error::ImportCycle(paths) => {
+			let (a, b) = (a.canonicalize().unwrap(), b.canonicalize().unwrap());
+			assert_eq!(paths, &vec![a.clone(), b, a]);
+		},
+		other => panic!("expected an import cycle, got {:?}", other)
+	}
+
+	// nothing is left half-loaded, so once the cycle is gone `a` can be imported
+	dir.write("b.qs", "`x` = 1;");
+	assert_eq!(try_run_file(&main).unwrap(), 1.into_object());
+}
+
+// sets `$module_path` to `dirs` while running `code`, and then clears it again
+fn with_module_path(dirs: &[&std::path::Path], code: &str) -> String {
+	let dirs = dirs.iter().map(|dir| format!("{:?}", dir.display().to_string())).collect::<Vec<_>>();
+	format!("`$module_path` = [{}]; `result` = ({}); `$module_path` = []; result", dirs.join(", "), code)
+}
+
+#[test]
+fn search_order() {
+	let dir = TempDir::new();
+	let (one, two) = (dir.path().join("one"), dir.path().join("two"));
+	dir.write("one/m.qs", "`x` = 1;");
+	dir.write("two/m.qs", "`x` = 2;");
+	dir.write("two/only_two.qs", "`x` = 2;");
+	dir.write("one/std/math.qs", "`pi` = 3;");
+
+	// `$module_path` is searched in order, and before the standard library
+	let main = dir.write("main.qs", &with_module_path(&[&one, &two],
+		"[(import:[\"m\"])::`x`, (import:[\"only_two\"])::`x`, (import:[\"std/math\"])::`pi`]"));
+	assert_eq!(try_run_file(&main).unwrap(), nums(&[1, 2, 3]));
+
+	// the importing file's directory comes first
+	dir.write("local/m.qs", "`x` = 0;");
+	let main = dir.write("local/main.qs", &with_module_path(&[&one, &two], "(import:[\"m\"])::`x`"));
+	assert_eq!(try_run_file(&main).unwrap(), 0.into_object());
+}
+
+#[test]
+fn module_path_can_change() {
+	let dir = TempDir::new();
+	let late = dir.path().join("late");
+	dir.write("late/added.qs", "`x` = 1;");
+
+	let main = dir.write("main.qs", &format!("
+		`$module_path` = [];
+		`before` = try:[{{ import:[\"added\"]; 1 }}, {{ 0 }}];
+		$module_path.`push`:[{:?}];
+		`after` = (import:[\"added\"])::`x`;
+		`$module_path` = [];
+		[before, after]
+	", late.display().to_string()));
+
+	assert_eq!(try_run_file(&main).unwrap(), nums(&[0, 1]));
+}
+
+#[test]
+fn bundled_modules() {
+	let dir = TempDir::new();
+	let main = dir.write("main.qs", "
+		`json` = import:[\"std/json\"];
+		`math` = import:[\"std/math\"];
+		[json.`stringify`:[[1, \"a\", null]], json.`parse`:[\"[1, 2]\"], math.`max`:[1, 2], __encode__]
+	");
+
+	// the functions `std/json` is built on aren't seen by anything else
+	assert_eq!(try_run_file(&main).unwrap(), vec![
+		"[1,\"a\",null]".to_string().into_object(),
+		nums(&[1, 2]),
+		2.into_object(),
+		Object::new_null()
+	].into_object());
+}
+
+#[test]
+fn reports_where_it_looked() {
+	let dir = TempDir::new();
+	let elsewhere = dir.path().join("elsewhere");
+	let main = dir.write("main.qs", &with_module_path(&[&elsewhere], "import:[\"nowhere\"]"));
+
+	let err = try_run_file(&main).unwrap_err();
+	let expected = vec![
+		dir.path().join("nowhere.qs").display().to_string(),
+		elsewhere.join("nowhere.qs").display().to_string(),
+		"the standard library, as `nowhere`".to_string()
+	];
+
+	match err.inner() {
+		Error::ModuleNotFound { name, tried } => {
+			assert_eq!(name, "nowhere");
+			assert_eq!(tried, &expected);
+		},
+		other => panic!("expected a missing module, got {:?}", other)
+	}
+
+	// the places are part of the message too
+	let message = err.inner().to_string();
+	assert!(expected.iter().all(|place| message.contains(place)), "{}", message);
+}
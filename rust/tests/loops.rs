@@ -0,0 +1,75 @@
+mod common;
+
+use common::{run, try_run, nums, text};
+use quest::{Object, Error, IntoObject};
+
+#[test]
+fn loop_returns_the_break_value() {
+	assert_eq!(run("
+		`seen` = [];
+		loop:[{
+			seen.`push`:[1];
+			(if:[seen.`len`! == 3, { break:[seen.`len`! * 10] }, { 0 }])!
+		}]
+	"), 30.into_object());
+
+	assert!(run("loop:[{ break! }]").is_null());
+}
+
+#[test]
+fn continue_skips_an_iteration() {
+	assert_eq!(run("
+		`seen` = [];
+		`result` = for:[[1, 2, 3, 4], { |n|
+			(if:[n == 2, { continue! }, { 0 }])!;
+			seen.`push`:[n]
+		}];
+		[result, seen]
+	"), vec![Object::new_null(), nums(&[1, 3, 4])].into_object());
+}
+
+#[test]
+fn labels_pass_through_inner_loops() {
+	assert_eq!(run("
+		`seen` = [];
+		`result` = for:[[1, 2, 3, 4], { |n|
+			loop:[{
+				(if:[n == 2, { continue:[\"outer\"] }, { 0 }])!;
+				(if:[n == 3, { break:[n * 100, \"outer\"] }, { 0 }])!;
+				seen.`push`:[n];
+				break!
+			}];
+			seen.`push`:[0]
+		}, \"outer\"];
+		[result, seen]
+	"), vec![300.into_object(), nums(&[1, 0])].into_object());
+}
+
+#[test]
+fn while_honours_labels() {
+	assert_eq!(run("
+		`seen` = [];
+		`result` = while:[{ seen.`len`! < 5 }, {
+			seen.`push`:[1];
+			(if:[seen.`len`! == 2, { loop:[{ break:[\"stopped\", \"outer\"] }] }, { 0 }])!
+		}, \"outer\"];
+		[result, seen.`len`!]
+	"), vec![text("stopped"), 2.into_object()].into_object());
+
+	// a label that no loop has escapes all of them
+	match try_run("while:[{ 1 == 1 }, { break:[1, \"nowhere\"] }, \"outer\"]").unwrap_err().inner() {
+		Error::Break { label, .. } => assert_eq!(label.as_ref().map(String::as_str), Some("nowhere")),
+		other => panic!("expected a break, got {:?}", other)
+	}
+}
+
+#[test]
+fn try_lets_break_through() {
+	assert_eq!(run("
+		`ran` = [];
+		`result` = loop:[{
+			try:[{ break:[5] }, { |err| 0 }, { ran.`push`:[1] }]
+		}];
+		[result, ran]
+	"), vec![5.into_object(), nums(&[1])].into_object());
+}
@@ -0,0 +1,78 @@
+mod common;
+
+use common::{run, try_run, nums, text};
+use quest::{Object, Error, IntoObject};
+
+fn error(code: &str) -> Error {
+	try_run(code).expect_err("script didn't fail")
+}
+
+#[test]
+fn missing_arguments_are_named() {
+	match error("`f` = { |a, b| a }; f:[1]").inner() {
+		Error::MissingArgument { pos, param, .. } => assert_eq!((*pos, *param), (1, Some("b"))),
+		other => panic!("expected a missing argument, got {:?}", other)
+	}
+
+	match error("`f` = { |a, key:| a }; f:[1, Map:[]]").inner() {
+		Error::MissingArgument { pos, param, .. } => assert_eq!((*pos, *param), (1, Some("key"))),
+		other => panic!("expected a missing argument, got {:?}", other)
+	}
+}
+
+#[test]
+fn defaults_see_earlier_params() {
+	assert_eq!(run("
+		`f` = { |a, b = a * 2, c = a + b| [a, b, c] };
+		[f:[3], f:[3, 1]]
+	"), vec![nums(&[3, 6, 9]), nums(&[3, 1, 4])].into_object());
+}
+
+#[test]
+fn rest_collects_extra_arguments() {
+	assert_eq!(run("
+		`f` = { |a, *rest| [a, rest] };
+		[f:[1, 2, 3], f:[1]]
+	"), vec![
+		vec![1.into_object(), nums(&[2, 3])].into_object(),
+		vec![1.into_object(), nums(&[])].into_object()
+	].into_object());
+}
+
+#[test]
+fn keywords_from_a_trailing_map() {
+	assert_eq!(run("
+		`f` = { |a, *rest, key: 3, other:| [a, rest, key, other] };
+		[f:[1, Map:[[\"other\", 2]]], f:[1, 2, Map:[[\"key\", 4], [\"other\", 5]]]]
+	"), vec![
+		vec![1.into_object(), nums(&[]), 3.into_object(), 2.into_object()].into_object(),
+		vec![1.into_object(), nums(&[2]), 4.into_object(), 5.into_object()].into_object()
+	].into_object());
+
+	match error("`f` = { |a, key: 3| a }; f:[1, Map:[[\"key\", 1], [\"nope\", 2]]]").inner() {
+		Error::BadArgument { msg, obj, .. } => {
+			assert_eq!(*msg, "unknown keyword argument");
+			assert_eq!(obj, &text("nope"));
+		},
+		other => panic!("expected a bad argument, got {:?}", other)
+	}
+}
+
+#[test]
+fn params_introspection() {
+	assert_eq!(run("
+		`f` = { |a, b = a * 2, *rest, key: 3| a };
+		`described` = [];
+		for:[f.`params`!, { |param|
+			described.`push`:[[param.`get`:[\"name\"], param.`get`:[\"kind\"], param.`get`:[\"default\"]]]
+		}];
+		described
+	"), vec![
+		vec![text("a"), text("positional"), Object::new_null()].into_object(),
+		vec![text("b"), text("positional"), text("a * 2")].into_object(),
+		vec![text("rest"), text("rest"), Object::new_null()].into_object(),
+		vec![text("key"), text("keyword"), text("3")].into_object()
+	].into_object());
+
+	assert_eq!(run("`g` = { a + b }; g.`params`!"), nums(&[]));
+}